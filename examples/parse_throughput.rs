@@ -0,0 +1,80 @@
+//! Compares the throughput of the parser backends on a generated multi-MB document.
+//!
+//! ```text
+//! cargo run --release --example parse_throughput
+//! ```
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use json::format::Formatter;
+use json::{JsonParser, Map, Number, SliceParser, Value};
+
+const RUNS: u32 = 5;
+
+fn main() {
+    let src = Formatter::standard().format(&document(20_000)).unwrap();
+    let mb = src.len() as f64 / (1024.0 * 1024.0);
+    println!("input: {mb:.1} MB, best of {RUNS} runs");
+
+    let results = [
+        bench("JsonParser over chars", || {
+            JsonParser::new(src.chars()).parse()
+        }),
+        bench("JsonParser over bytes", || {
+            JsonParser::from_slice(src.as_bytes()).parse()
+        }),
+        bench("JsonParser over a reader", || {
+            JsonParser::from_reader(src.as_bytes()).parse()
+        }),
+        bench("SliceParser", || SliceParser::new(src.as_bytes()).parse()),
+        bench("SliceParser, borrowed", || {
+            SliceParser::new(src.as_bytes()).parse_borrowed()
+        }),
+    ];
+
+    let baseline = results[0].1;
+    for (name, time) in results {
+        let throughput = mb / time.as_secs_f64();
+        let speedup = baseline.as_secs_f64() / time.as_secs_f64();
+        println!("{name:<26} {time:>10.2?} {throughput:>8.1} MB/s {speedup:>6.2}x");
+    }
+}
+
+fn bench<V, E: std::fmt::Debug>(
+    name: &'static str,
+    mut parse: impl FnMut() -> Result<V, E>,
+) -> (&'static str, Duration) {
+    let mut best = Duration::MAX;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        let value = parse().expect("document should be valid");
+        best = best.min(start.elapsed());
+        black_box(value);
+    }
+    (name, best)
+}
+
+/// An array of records mixing every kind of value, like a typical api export.
+fn document(records: usize) -> Value {
+    let values = (0..records)
+        .map(|i| {
+            let mut map = Map::new();
+            map.insert(String::from("id"), Value::Number(Number::from(i)));
+            map.insert(
+                String::from("name"),
+                Value::String(format!("user {i} \"quoted\" ünïcödé")),
+            );
+            map.insert(
+                String::from("score"),
+                Value::Number(Number::from(i as f64 * 1.25)),
+            );
+            map.insert(String::from("active"), Value::Bool(i % 2 == 0));
+            map.insert(String::from("manager"), Value::Null);
+            let tags = (0..5).map(|t| Value::String(format!("tag-{t}"))).collect();
+            map.insert(String::from("tags"), Value::Array(tags));
+            Value::Object(map)
+        })
+        .collect();
+    Value::Array(values)
+}
@@ -0,0 +1,124 @@
+use std::fmt;
+use std::iter::FromIterator;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::{slice, vec};
+
+use super::{Value, drop_nested};
+
+/// The elements of a json array.
+///
+/// Derefs to the `Vec` that holds the elements, so it is used like one. Unlike a `Vec`,
+/// it drops nested arrays and objects iteratively, so dropping a deeply nested value
+/// cannot overflow the thread stack.
+#[derive(Clone, Default, PartialEq)]
+pub struct Array {
+    vec: Vec<Value>,
+}
+
+impl Array {
+    pub fn new() -> Self {
+        Self { vec: Vec::new() }
+    }
+
+    /// Takes the elements out of the array.
+    pub fn into_vec(mut self) -> Vec<Value> {
+        mem::take(&mut self.vec)
+    }
+}
+
+impl Drop for Array {
+    fn drop(&mut self) {
+        if self.vec.iter().any(Value::is_container) {
+            drop_nested(mem::take(&mut self.vec));
+        }
+    }
+}
+
+impl Deref for Array {
+    type Target = Vec<Value>;
+
+    fn deref(&self) -> &Vec<Value> {
+        &self.vec
+    }
+}
+
+impl DerefMut for Array {
+    fn deref_mut(&mut self) -> &mut Vec<Value> {
+        &mut self.vec
+    }
+}
+
+impl fmt::Debug for Array {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.vec.fmt(f)
+    }
+}
+
+impl From<Vec<Value>> for Array {
+    fn from(vec: Vec<Value>) -> Self {
+        Self { vec }
+    }
+}
+
+impl From<Array> for Vec<Value> {
+    fn from(array: Array) -> Self {
+        array.into_vec()
+    }
+}
+
+impl FromIterator<Value> for Array {
+    fn from_iter<I: IntoIterator<Item = Value>>(iter: I) -> Self {
+        Self {
+            vec: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a Array {
+    type Item = &'a Value;
+    type IntoIter = slice::Iter<'a, Value>;
+
+    fn into_iter(self) -> slice::Iter<'a, Value> {
+        self.vec.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Array {
+    type Item = &'a mut Value;
+    type IntoIter = slice::IterMut<'a, Value>;
+
+    fn into_iter(self) -> slice::IterMut<'a, Value> {
+        self.vec.iter_mut()
+    }
+}
+
+impl IntoIterator for Array {
+    type Item = Value;
+    type IntoIter = vec::IntoIter<Value>;
+
+    fn into_iter(self) -> vec::IntoIter<Value> {
+        self.into_vec().into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn array_works() {
+        let mut array: Array = [Value::Null, Value::Bool(true)].into_iter().collect();
+        array.push(Value::Array(Array::new()));
+        assert_eq!(array.len(), 3);
+        assert_eq!(array[1], Value::Bool(true));
+        assert_eq!(format!("{array:?}"), "[Null, Bool(true), Array([])]");
+
+        let Value::Array(nested) = array.pop().unwrap() else {
+            panic!("expected an array");
+        };
+        assert!(nested.is_empty());
+        let vec: Vec<Value> = array.into();
+        assert_eq!(vec, [Value::Null, Value::Bool(true)]);
+    }
+}
@@ -0,0 +1,474 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::{Deref, DerefMut};
+use std::{mem, slice, vec};
+
+use super::{DuplicateKeys, JsonParserError, Map, Number, SliceParser, Value};
+
+/// A json value whose strings and keys borrow from the input they were parsed from.
+/// Only strings with escapes are copied, to unescape them.
+///
+/// Objects keep their members in document order. Duplicate keys are resolved by the
+/// parser, so a key appears at most once, at the position of its first occurrence.
+///
+/// ```
+/// use json::borrowed::{self, BorrowedValue};
+///
+/// let src = r#"{"name": "plain", "escaped": "a\nb"}"#;
+/// let value = borrowed::from_str(src).unwrap();
+/// assert_eq!(value.get("name").and_then(BorrowedValue::as_str), Some("plain"));
+/// assert_eq!(value.get("escaped").and_then(BorrowedValue::as_str), Some("a\nb"));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum BorrowedValue<'a> {
+    #[default]
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(Cow<'a, str>),
+    Array(Array<'a>),
+    Object(Object<'a>),
+}
+
+impl<'a> BorrowedValue<'a> {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            BorrowedValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<&Number> {
+        match self {
+            BorrowedValue::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[BorrowedValue<'a>]> {
+        match self {
+            BorrowedValue::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&[(Cow<'a, str>, BorrowedValue<'a>)]> {
+        match self {
+            BorrowedValue::Object(members) => Some(members),
+            _ => None,
+        }
+    }
+
+    /// The value of the member `key`, when this is an object that has it.
+    pub fn get(&self, key: &str) -> Option<&BorrowedValue<'a>> {
+        self.as_object()?
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    /// Takes the string out of the value, like [`Value::into_string`].
+    pub fn into_string(self) -> Option<Cow<'a, str>> {
+        match self {
+            BorrowedValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn into_array(self) -> Option<Vec<BorrowedValue<'a>>> {
+        match self {
+            BorrowedValue::Array(values) => Some(values.into_vec()),
+            _ => None,
+        }
+    }
+
+    pub fn into_object(self) -> Option<Vec<(Cow<'a, str>, BorrowedValue<'a>)>> {
+        match self {
+            BorrowedValue::Object(members) => Some(members.into_vec()),
+            _ => None,
+        }
+    }
+
+    /// Copies the borrowed strings to build an owned [`Value`]. Nested values are
+    /// converted iteratively, so deep nesting cannot overflow the thread stack.
+    pub fn into_owned(self) -> Value {
+        let mut stack = Vec::new();
+        let mut value = self;
+        loop {
+            let mut started = value.is_container();
+            let mut owned = match value {
+                BorrowedValue::Null => Value::Null,
+                BorrowedValue::Bool(b) => Value::Bool(b),
+                BorrowedValue::Number(n) => Value::Number(n),
+                BorrowedValue::String(s) => Value::String(s.into_owned()),
+                BorrowedValue::Array(values) => {
+                    let done = Vec::with_capacity(values.len());
+                    stack.push(Pending::Array(values.into_iter(), done));
+                    Value::Null
+                }
+                BorrowedValue::Object(members) => {
+                    let members = members.into_iter();
+                    stack.push(Pending::Object(members, Map::new(), String::new()));
+                    Value::Null
+                }
+            };
+
+            // attach the converted value to its container, finishing every container
+            // that has no values left
+            value = loop {
+                match stack.last_mut() {
+                    None => return owned,
+                    Some(Pending::Array(rest, done)) => {
+                        if !mem::take(&mut started) {
+                            done.push(owned);
+                        }
+                        if let Some(next) = rest.next() {
+                            break next;
+                        }
+                        owned = Value::Array(mem::take(done).into());
+                    }
+                    Some(Pending::Object(rest, done, key)) => {
+                        if !mem::take(&mut started) {
+                            done.insert(mem::take(key), owned);
+                        }
+                        if let Some((k, next)) = rest.next() {
+                            *key = k.into_owned();
+                            break next;
+                        }
+                        owned = Value::Object(mem::take(done));
+                    }
+                }
+                stack.pop();
+            };
+        }
+    }
+
+    fn is_container(&self) -> bool {
+        matches!(self, BorrowedValue::Array(_) | BorrowedValue::Object(_))
+    }
+}
+
+/// A container that [`BorrowedValue::into_owned`] is still converting.
+enum Pending<'a> {
+    Array(vec::IntoIter<BorrowedValue<'a>>, Vec<Value>),
+    /// The key of the member being converted comes last.
+    Object(
+        vec::IntoIter<(Cow<'a, str>, BorrowedValue<'a>)>,
+        Map,
+        String,
+    ),
+}
+
+/// The elements of a borrowed json array. Derefs to the `Vec` that holds them, and
+/// drops nested arrays and objects iteratively, like [`crate::Array`].
+#[derive(Clone, Default, PartialEq)]
+pub struct Array<'a> {
+    vec: Vec<BorrowedValue<'a>>,
+}
+
+impl<'a> Array<'a> {
+    /// Takes the elements out of the array.
+    pub fn into_vec(mut self) -> Vec<BorrowedValue<'a>> {
+        mem::take(&mut self.vec)
+    }
+}
+
+impl Drop for Array<'_> {
+    fn drop(&mut self) {
+        if self.vec.iter().any(BorrowedValue::is_container) {
+            drop_nested(mem::take(&mut self.vec));
+        }
+    }
+}
+
+impl<'a> Deref for Array<'a> {
+    type Target = Vec<BorrowedValue<'a>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.vec
+    }
+}
+
+impl DerefMut for Array<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.vec
+    }
+}
+
+impl fmt::Debug for Array<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.vec.fmt(f)
+    }
+}
+
+impl<'a> From<Vec<BorrowedValue<'a>>> for Array<'a> {
+    fn from(vec: Vec<BorrowedValue<'a>>) -> Self {
+        Self { vec }
+    }
+}
+
+impl<'a> FromIterator<BorrowedValue<'a>> for Array<'a> {
+    fn from_iter<I: IntoIterator<Item = BorrowedValue<'a>>>(iter: I) -> Self {
+        Self {
+            vec: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<'a, 'b> IntoIterator for &'b Array<'a> {
+    type Item = &'b BorrowedValue<'a>;
+    type IntoIter = slice::Iter<'b, BorrowedValue<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.vec.iter()
+    }
+}
+
+impl<'a> IntoIterator for Array<'a> {
+    type Item = BorrowedValue<'a>;
+    type IntoIter = vec::IntoIter<BorrowedValue<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_vec().into_iter()
+    }
+}
+
+/// The members of a borrowed json object, in document order. Derefs to the `Vec` that
+/// holds them, and drops nested arrays and objects iteratively, like [`Map`].
+#[derive(Clone, Default, PartialEq)]
+pub struct Object<'a> {
+    members: Vec<(Cow<'a, str>, BorrowedValue<'a>)>,
+}
+
+impl<'a> Object<'a> {
+    /// Takes the members out of the object.
+    pub fn into_vec(mut self) -> Vec<(Cow<'a, str>, BorrowedValue<'a>)> {
+        mem::take(&mut self.members)
+    }
+}
+
+impl Drop for Object<'_> {
+    fn drop(&mut self) {
+        if self.members.iter().any(|(_, v)| v.is_container()) {
+            drop_nested(
+                mem::take(&mut self.members)
+                    .into_iter()
+                    .map(|(_, v)| v)
+                    .collect(),
+            );
+        }
+    }
+}
+
+impl<'a> Deref for Object<'a> {
+    type Target = Vec<(Cow<'a, str>, BorrowedValue<'a>)>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.members
+    }
+}
+
+impl DerefMut for Object<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.members
+    }
+}
+
+impl fmt::Debug for Object<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.members.fmt(f)
+    }
+}
+
+impl<'a> From<Vec<(Cow<'a, str>, BorrowedValue<'a>)>> for Object<'a> {
+    fn from(members: Vec<(Cow<'a, str>, BorrowedValue<'a>)>) -> Self {
+        Self { members }
+    }
+}
+
+impl<'a> FromIterator<(Cow<'a, str>, BorrowedValue<'a>)> for Object<'a> {
+    fn from_iter<I: IntoIterator<Item = (Cow<'a, str>, BorrowedValue<'a>)>>(iter: I) -> Self {
+        Self {
+            members: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<'a, 'b> IntoIterator for &'b Object<'a> {
+    type Item = &'b (Cow<'a, str>, BorrowedValue<'a>);
+    type IntoIter = slice::Iter<'b, (Cow<'a, str>, BorrowedValue<'a>)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.members.iter()
+    }
+}
+
+impl<'a> IntoIterator for Object<'a> {
+    type Item = (Cow<'a, str>, BorrowedValue<'a>);
+    type IntoIter = vec::IntoIter<(Cow<'a, str>, BorrowedValue<'a>)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_vec().into_iter()
+    }
+}
+
+/// Drops `stack` and the values nested in it with a loop instead of recursion.
+fn drop_nested(mut stack: Vec<BorrowedValue<'_>>) {
+    while let Some(mut value) = stack.pop() {
+        match &mut value {
+            BorrowedValue::Array(values) => stack.append(values),
+            BorrowedValue::Object(members) => {
+                stack.extend(mem::take(members).into_iter().map(|(_, v)| v))
+            }
+            _ => {}
+        }
+    }
+}
+
+impl From<BorrowedValue<'_>> for Value {
+    fn from(value: BorrowedValue<'_>) -> Self {
+        value.into_owned()
+    }
+}
+
+/// Parses `src` as a single json document, like [`crate::from_str`], borrowing strings
+/// from it.
+pub fn from_str(src: &str) -> Result<BorrowedValue<'_>, JsonParserError> {
+    from_slice(src.as_bytes())
+}
+
+/// Parses a single json value from UTF-8 bytes, like [`crate::from_slice`], borrowing
+/// strings from them.
+pub fn from_slice(src: &[u8]) -> Result<BorrowedValue<'_>, JsonParserError> {
+    let mut parser = SliceParser::new(src);
+    let value = parser.parse_borrowed()?;
+    parser.end()?;
+    Ok(value)
+}
+
+/// An object that [`SliceParser::parse_borrowed`] is still parsing.
+#[derive(Default)]
+pub(crate) struct ObjectFrame<'a> {
+    pub(crate) members: Vec<(Cow<'a, str>, BorrowedValue<'a>)>,
+    /// Key of the member being parsed, and the offset it starts at.
+    pub(crate) key: Cow<'a, str>,
+    pub(crate) offset: usize,
+    /// Index in `members` and offset of the first occurrence of every key.
+    pub(crate) indices: HashMap<Cow<'a, str>, (usize, usize)>,
+    /// Indices of members whose values were already collected into an array, only
+    /// tracked for [`DuplicateKeys::Collect`].
+    collected: HashSet<usize>,
+}
+
+impl<'a> ObjectFrame<'a> {
+    /// Adds the member whose key was parsed last.
+    pub(crate) fn insert(&mut self, value: BorrowedValue<'a>, duplicate_keys: DuplicateKeys) {
+        let key = mem::take(&mut self.key);
+        let Some(&(index, _)) = self.indices.get(&key) else {
+            self.indices
+                .insert(key.clone(), (self.members.len(), self.offset));
+            self.members.push((key, value));
+            return;
+        };
+        let existing = &mut self.members[index].1;
+        match duplicate_keys {
+            DuplicateKeys::Error | DuplicateKeys::LastWins => *existing = value,
+            DuplicateKeys::FirstWins => {}
+            DuplicateKeys::Collect => match existing {
+                BorrowedValue::Array(values) if self.collected.contains(&index) => {
+                    values.push(value);
+                }
+                _ => {
+                    let first = mem::take(existing);
+                    *existing = BorrowedValue::Array(vec![first, value].into());
+                    self.collected.insert(index);
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_str as from_str_owned;
+
+    #[test]
+    fn borrowed_value_borrows_strings() {
+        let src = r#"{"plain": "text", "esc\u0061ped": ["a\"b", "ünïcödé", ""]}"#;
+        let value = from_str(src).unwrap();
+        let members = value.as_object().unwrap();
+        assert_eq!(members[0].0, "plain");
+        assert!(matches!(members[0].0, Cow::Borrowed(_)));
+        let BorrowedValue::String(text) = &members[0].1 else {
+            panic!("expected a string");
+        };
+        assert!(matches!(text, Cow::Borrowed(_)));
+
+        assert_eq!(members[1].0, "escaped");
+        assert!(!matches!(members[1].0, Cow::Borrowed(_)));
+        let values = members[1].1.as_array().unwrap();
+        assert_eq!(values[0].as_str(), Some("a\"b"));
+        assert_eq!(values[1].as_str(), Some("ünïcödé"));
+        assert_eq!(values[2].as_str(), Some(""));
+        let strings: Vec<_> = values
+            .iter()
+            .map(|v| matches!(v.clone().into_string(), Some(Cow::Borrowed(_))))
+            .collect();
+        assert_eq!(strings, [false, true, true]);
+    }
+
+    #[test]
+    fn borrowed_value_keeps_document_order() {
+        let src = r#"{"b": 1, "a": 2, "b": 3, "c": {"z": null, "y": true}}"#;
+        let value = from_str(src).unwrap();
+        let keys: Vec<_> = value
+            .as_object()
+            .unwrap()
+            .iter()
+            .map(|(k, _)| k.as_ref())
+            .collect();
+        assert_eq!(keys, ["b", "a", "c"]);
+        assert_eq!(
+            value.get("b").and_then(BorrowedValue::as_number),
+            Some(&Number::from(3))
+        );
+        assert_eq!(value.get("missing"), None);
+
+        let mut parser = SliceParser::new(src.as_bytes()).duplicate_keys(DuplicateKeys::Collect);
+        let value = parser.parse_borrowed().unwrap();
+        let collected = value.get("b").and_then(BorrowedValue::as_array).unwrap();
+        assert_eq!(collected.len(), 2);
+    }
+
+    #[test]
+    fn borrowed_value_into_owned_works() {
+        let src = r#"[{"a": [1, -2.5, "x\ty"], "b": {}}, [], true, null, "s"]"#;
+        let value = from_str(src).unwrap();
+        assert_eq!(Value::from(value.clone()), from_str_owned(src).unwrap());
+        assert_eq!(
+            BorrowedValue::String(Cow::Borrowed("s")).into_owned(),
+            Value::String(String::from("s"))
+        );
+    }
+
+    #[test]
+    fn borrowed_value_handles_deep_nesting() {
+        let depth = 100_000;
+        let mixed = format!("{}null{}", "[{\"k\":".repeat(depth), "}]".repeat(depth));
+        let arrays = format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        for src in [mixed, arrays] {
+            let parse = || {
+                SliceParser::new(src.as_bytes())
+                    .max_depth(usize::MAX)
+                    .parse_borrowed()
+                    .unwrap()
+            };
+            drop(parse());
+            let owned = parse().into_owned();
+            assert_eq!(owned.into_array().map(|values| values.len()), Some(1));
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use std::fmt;
+
+use super::{ErrorKind, JsonParserError};
+
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const CYAN: &str = "\x1b[1;36m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// Renders a [`JsonParserError`] together with the source it came from, in a format
+/// similar to rustc diagnostics:
+///
+/// ```text
+/// error: expected either array value separator ',' or end of array character ']', but received '2'
+///  --> line 1, column 4
+///   |
+/// 1 | [1 2]
+///   |    ^ expected ',' or ']'
+///   |
+///   = hint: did you forget a comma?
+/// ```
+#[derive(Clone, Debug)]
+pub struct Diagnostic<'a> {
+    err: &'a JsonParserError,
+    src: &'a str,
+    color: bool,
+}
+
+impl<'a> Diagnostic<'a> {
+    /// `src` must be the same input the error was produced from.
+    pub fn new(err: &'a JsonParserError, src: &'a str) -> Self {
+        Self {
+            err,
+            src,
+            color: false,
+        }
+    }
+
+    /// Enables ANSI colour escapes in the rendered output.
+    pub fn color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    fn paint(&self, style: &'static str) -> &'static str {
+        if self.color { style } else { "" }
+    }
+
+    /// The line the error points at, without its line terminator.
+    fn source_line(&self) -> &'a str {
+        let line = self.err.line() as usize;
+        self.src
+            .split('\n')
+            .nth(line.saturating_sub(1))
+            .map(|l| l.strip_suffix('\r').unwrap_or(l))
+            .unwrap_or("")
+    }
+
+    /// Number of characters to underline, starting at the error column.
+    fn span_len(&self, line: &str) -> usize {
+        let rest = line.chars().skip(self.err.column() as usize - 1);
+        match self.err.kind() {
+            ErrorKind::InvalidNumber | ErrorKind::NumberOutOfRange => rest
+                .take_while(|ch| matches!(ch, '0'..='9' | '-' | '+' | '.' | 'e' | 'E'))
+                .count()
+                .max(1),
+            ErrorKind::InvalidUnicode => {
+                let escape: Vec<char> = rest.take(12).collect();
+                let is_pair = escape.len() == 12 && escape[6..8] == ['\\', 'u'];
+                if is_pair {
+                    12
+                } else {
+                    escape.len().clamp(1, 6)
+                }
+            }
+            _ => 1,
+        }
+    }
+
+    fn hint(&self) -> Option<&'static str> {
+        let err = self.err;
+        let hint = match (err.kind(), err.expected(), err.found()) {
+            (ErrorKind::UnexpectedChar, Some("',' or ']'" | "',' or '}'"), Some(ch))
+                if starts_value(ch) =>
+            {
+                "did you forget a comma?"
+            }
+            (ErrorKind::UnexpectedChar, Some("string"), Some('\'')) => {
+                "object keys must use double quotes"
+            }
+            (ErrorKind::UnexpectedChar, Some("string"), _) => {
+                "object keys must be strings in double quotes"
+            }
+            (ErrorKind::UnexpectedChar, Some("':'"), _) => "object keys must be followed by ':'",
+            (ErrorKind::UnexpectedChar, Some("value"), Some('\'')) => {
+                "strings must use double quotes"
+            }
+            (ErrorKind::UnexpectedChar, Some("value"), Some(']' | '}')) => {
+                "trailing commas are not allowed"
+            }
+            (ErrorKind::UnexpectedEof, _, _) => {
+                "the input ended early, check for unclosed brackets or quotes"
+            }
+            (ErrorKind::InvalidNumber, None, _) => "remove the leading zeros",
+            (ErrorKind::NumberOutOfRange, _, _) => {
+                "enable arbitrary precision to keep numbers of any size"
+            }
+            (ErrorKind::InvalidEscape, _, _) => {
+                r#"valid escapes are \", \\, \/, \b, \f, \n, \r, \t and \uXXXX"#
+            }
+            (ErrorKind::InvalidUnicode, _, _) => {
+                r"surrogates must come in pairs, e.g. \uD83D\uDE00"
+            }
+            (ErrorKind::ControlCharacter, _, _) => {
+                r"control characters must be escaped, e.g. \n for a newline"
+            }
+            (ErrorKind::TrailingCharacters, _, _) => {
+                "only one root value is allowed, wrap multiple values in an array"
+            }
+            (ErrorKind::DuplicateKey, _, _) => "remove or rename one of the members",
+            (ErrorKind::InvalidUtf8, _, _) => "json text must be encoded as UTF-8",
+            (ErrorKind::DepthLimit, _, _) => {
+                "arrays and objects are nested too deep, raise the parser's max depth if this is expected"
+            }
+            _ => return None,
+        };
+        Some(hint)
+    }
+}
+
+fn starts_value(ch: char) -> bool {
+    matches!(ch, '"' | '{' | '[' | 't' | 'f' | 'n' | '-' | '0'..='9')
+}
+
+impl fmt::Display for Diagnostic<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (red, blue, cyan, bold, reset) = (
+            self.paint(RED),
+            self.paint(BLUE),
+            self.paint(CYAN),
+            self.paint(BOLD),
+            self.paint(RESET),
+        );
+        let line_no = self.err.line().to_string();
+        let gutter = " ".repeat(line_no.len());
+
+        writeln!(f, "{red}error{reset}{bold}: {}{reset}", self.err.message())?;
+        writeln!(
+            f,
+            "{gutter}{blue}-->{reset} line {}, column {}",
+            self.err.line(),
+            self.err.column()
+        )?;
+        writeln!(f, "{gutter} {blue}|{reset}")?;
+
+        // tabs are replaced one for one so the caret stays aligned
+        let line = self.source_line().replace('\t', " ");
+        writeln!(f, "{blue}{line_no} |{reset} {line}")?;
+
+        let padding = " ".repeat(self.err.column() as usize - 1);
+        let underline = "^".repeat(self.span_len(&line));
+        write!(f, "{gutter} {blue}|{reset} {padding}{red}{underline}")?;
+        if let Some(expected) = self.err.expected() {
+            write!(f, " expected {expected}")?;
+        }
+        writeln!(f, "{reset}")?;
+
+        if let Some(hint) = self.hint() {
+            writeln!(f, "{gutter} {blue}|{reset}")?;
+            writeln!(f, "{gutter} {blue}={reset} {cyan}hint{reset}: {hint}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_str;
+
+    fn render(src: &str) -> String {
+        let err = from_str(src).unwrap_err();
+        Diagnostic::new(&err, src).to_string()
+    }
+
+    #[test]
+    fn diagnostic_works() {
+        let out = render("[1 2]");
+        let expected = "\
+error: expected either array value separator ',' or end of array character ']', but received '2'
+ --> line 1, column 4
+  |
+1 | [1 2]
+  |    ^ expected ',' or ']'
+  |
+  = hint: did you forget a comma?
+";
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn diagnostic_underlines_span() {
+        let src = "{\r\n  \"a\": 1,\r\n  \"b\": 1e999\r\n}";
+        let out = render(src);
+        let expected = "\
+error: number '1e999' is out of range
+ --> line 3, column 8
+  |
+3 |   \"b\": 1e999
+  |        ^^^^^
+  |
+  = hint: enable arbitrary precision to keep numbers of any size
+";
+        assert_eq!(out, expected);
+
+        let out = render(r#"["\uD83DA"]"#);
+        assert!(
+            out.contains("\n  |   ^^^^^^ expected trailing surrogate\n"),
+            "{out}"
+        );
+    }
+
+    #[test]
+    fn diagnostic_eof_and_wide_gutter() {
+        let src = format!("{}[1,", "\n".repeat(9));
+        let out = render(&src);
+        let expected = "\
+error: unexpected end of input
+  --> line 10, column 4
+   |
+10 | [1,
+   |    ^ expected value
+   |
+   = hint: the input ended early, check for unclosed brackets or quotes
+";
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn diagnostic_color_works() {
+        let src = "{'a': 1}";
+        let err = from_str(src).unwrap_err();
+        let out = Diagnostic::new(&err, src).color(true).to_string();
+        assert!(out.starts_with("\x1b[1;31merror\x1b[0m"));
+        assert!(out.contains("object keys must use double quotes"));
+    }
+}
@@ -0,0 +1,282 @@
+use std::mem;
+use std::ops::Range;
+
+use super::{ErrorKind, JsonParser, JsonParserError, Source, Value};
+
+/// How the documents of a stream are delimited, see [`JsonParser::documents`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Framing {
+    /// Values one after the other, optionally separated by whitespace, e.g.
+    /// `{"a":1}{"b":2}`.
+    Concatenated,
+    /// An RFC 7464 json text sequence (`application/json-seq`): every value is preceded
+    /// by an RS character (U+001E) and followed by a line feed.
+    JsonSeq,
+}
+
+/// The record separator that starts every record of a json text sequence.
+const RS: char = '\u{1e}';
+
+/// An iterator over the documents of a stream, created with [`JsonParser::documents`].
+/// Yields every document with its byte range in the input, leading and trailing
+/// whitespace excluded.
+///
+/// A concatenated stream cannot be resynchronized, so iteration stops after the first
+/// error. A json text sequence recovers from invalid and truncated records as RFC 7464
+/// specifies: the error is reported and parsing resumes at the record separator that
+/// ended the failed record, even when it appeared inside a string.
+/// Errors reading the input or decoding it end both.
+pub struct Documents<T: Source> {
+    parser: JsonParser<T>,
+    framing: Framing,
+    /// Whether the rest of a failed record has to be skipped.
+    resync: bool,
+    /// Whether a failed record ended at a record separator, which the parser may have
+    /// consumed, e.g. as a character of an unterminated string.
+    at_separator: bool,
+    done: bool,
+}
+
+impl<T: Source> Documents<T> {
+    pub(crate) fn new(parser: JsonParser<T>, framing: Framing) -> Self {
+        Self {
+            parser,
+            framing,
+            resync: false,
+            at_separator: false,
+            done: false,
+        }
+    }
+
+    fn next_concatenated(&mut self) -> Result<Option<(Value, Range<usize>)>, JsonParserError> {
+        self.parser.skip_whitespace()?;
+        if self.parser.peek()?.is_none() {
+            return Ok(None);
+        }
+        self.parse().map(Some)
+    }
+
+    fn next_record(&mut self) -> Result<Option<(Value, Range<usize>)>, JsonParserError> {
+        // a failed record that consumed the separator ending it was followed by this one
+        let mut separated = false;
+        if mem::take(&mut self.at_separator) && self.parser.peek()? != Some(RS) {
+            self.resync = false;
+            separated = true;
+        }
+
+        // move past the record separator, skipping empty records and what is left of a
+        // failed one
+        loop {
+            if separated {
+                self.parser.skip_whitespace()?;
+                if !matches!(self.parser.peek()?, None | Some(RS)) {
+                    break;
+                }
+                separated = false;
+            }
+            match self.parser.peek()? {
+                None => return Ok(None),
+                Some(RS) => {
+                    self.parser.eat()?;
+                    self.resync = false;
+                    separated = true;
+                }
+                Some(ch) if self.resync || self.parser.is_whitespace(ch) => {
+                    self.parser.eat()?;
+                }
+                Some(ch) => {
+                    let msg = format!(
+                        "expected record separator '\\u001E' before a json text but received '{ch}'"
+                    );
+                    return Err(self.parser.unexpected(ch, "record separator", msg));
+                }
+            }
+        }
+
+        let (value, range) = self.parse()?;
+        let ends_early = matches!(value, Value::Number(_) | Value::Bool(_) | Value::Null)
+            && !self
+                .parser
+                .peek()?
+                .is_some_and(|ch| self.parser.is_whitespace(ch));
+        if ends_early {
+            let msg = "json text may have been truncated, top-level numbers, booleans and nulls must be followed by whitespace";
+            let err = self.parser.error(ErrorKind::UnexpectedEof, msg);
+            return Err(err.with_expected("whitespace"));
+        }
+
+        self.parser.skip_whitespace()?;
+        match self.parser.peek()? {
+            None | Some(RS) => Ok(Some((value, range))),
+            Some(ch) => {
+                let msg = format!("trailing characters after json text, received '{ch}'");
+                let err = self.parser.error(ErrorKind::TrailingCharacters, msg);
+                Err(err.with_found(ch))
+            }
+        }
+    }
+
+    fn parse(&mut self) -> Result<(Value, Range<usize>), JsonParserError> {
+        let start = self.parser.pos.offset;
+        let value = self.parser.parse()?;
+        Ok((value, start..self.parser.pos.offset))
+    }
+}
+
+impl<T: Source> Iterator for Documents<T> {
+    type Item = Result<(Value, Range<usize>), JsonParserError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let next = match self.framing {
+            Framing::Concatenated => self.next_concatenated(),
+            Framing::JsonSeq => self.next_record(),
+        };
+        match next {
+            Ok(Some(document)) => Some(Ok(document)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(err) => {
+                self.resync = true;
+                self.at_separator = err.found() == Some(RS);
+                self.done = self.framing == Framing::Concatenated
+                    || matches!(err.kind(), ErrorKind::Io | ErrorKind::InvalidUtf8);
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Number, from_str};
+
+    fn read(src: &str, framing: Framing) -> Vec<Result<(Value, Range<usize>), ErrorKind>> {
+        JsonParser::new(src.chars())
+            .documents(framing)
+            .map(|document| document.map_err(|err| err.kind()))
+            .collect()
+    }
+
+    fn number(n: i64) -> Value {
+        Value::Number(Number::from(n))
+    }
+
+    #[test]
+    fn concatenated_documents_work() {
+        let src = "{\"a\":1}{\"b\":2} [3]\"x\"4\n5 true";
+        let documents: Vec<_> = read(src, Framing::Concatenated)
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            documents,
+            [
+                (from_str("{\"a\":1}").unwrap(), 0..7),
+                (from_str("{\"b\":2}").unwrap(), 7..14),
+                (from_str("[3]").unwrap(), 15..18),
+                (Value::String(String::from("x")), 18..21),
+                (number(4), 21..22),
+                (number(5), 23..24),
+                (Value::Bool(true), 25..29),
+            ]
+        );
+        for (src, range) in [("\"é\" 1", 5..6), ("  [] ", 2..4)] {
+            let last = read(src, Framing::Concatenated).pop().unwrap();
+            assert_eq!(last.unwrap().1, range);
+        }
+        assert!(read(" \n ", Framing::Concatenated).is_empty());
+    }
+
+    #[test]
+    fn concatenated_documents_stop_after_errors() {
+        let documents = read("[1] {\"a\" 1} [2]", Framing::Concatenated);
+        assert_eq!(documents.len(), 2);
+        assert_eq!(documents[0], Ok((from_str("[1]").unwrap(), 0..3)));
+        assert_eq!(documents[1], Err(ErrorKind::UnexpectedChar));
+    }
+
+    #[test]
+    fn json_seq_documents_work() {
+        let src = "\u{1e}{\"a\":1}\n\u{1e}\u{1e}\n\u{1e} 2 \n\u{1e}\"s\"\n";
+        let documents: Vec<_> = read(src, Framing::JsonSeq)
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            documents,
+            [
+                (from_str("{\"a\":1}").unwrap(), 1..8),
+                (number(2), 14..15),
+                (Value::String(String::from("s")), 18..21),
+            ]
+        );
+        assert!(read("", Framing::JsonSeq).is_empty());
+        assert!(read("\u{1e}\n\u{1e}", Framing::JsonSeq).is_empty());
+    }
+
+    #[test]
+    fn json_seq_documents_recover_from_invalid_records() {
+        let src = "x\u{1e}1\n\u{1e}[1,2\n\u{1e}3\u{1e}true\n\u{1e}{} x\n\u{1e}[4]";
+        let documents = read(src, Framing::JsonSeq);
+        assert_eq!(
+            documents,
+            [
+                Err(ErrorKind::UnexpectedChar),
+                Ok((number(1), 2..3)),
+                // truncated records
+                Err(ErrorKind::UnexpectedChar),
+                Err(ErrorKind::UnexpectedEof),
+                Ok((Value::Bool(true), 13..17)),
+                Err(ErrorKind::TrailingCharacters),
+                Ok((from_str("[4]").unwrap(), 25..28)),
+            ]
+        );
+        // a record cut short inside a string fails at the separator ending it
+        let src = "\u{1e}\"abc\u{1e}1\n\u{1e}2\n";
+        let documents = read(src, Framing::JsonSeq);
+        assert_eq!(
+            documents,
+            [
+                Err(ErrorKind::ControlCharacter),
+                Ok((number(1), 6..7)),
+                Ok((number(2), 9..10)),
+            ]
+        );
+        let src = "\u{1e}[\"abc\u{1e}{\"a\":1}\n\u{1e}\"\\\u{1e}2\n\u{1e}\"\\uD800\\\u{1e}3\n";
+        let documents = read(src, Framing::JsonSeq);
+        assert_eq!(
+            documents,
+            [
+                Err(ErrorKind::ControlCharacter),
+                Ok((from_str("{\"a\":1}").unwrap(), 7..14)),
+                Err(ErrorKind::InvalidEscape),
+                Ok((number(2), 19..20)),
+                Err(ErrorKind::InvalidUnicode),
+                Ok((number(3), 31..32)),
+            ]
+        );
+        // the last record can be truncated too
+        let last = read("\u{1e}1\n\u{1e}12", Framing::JsonSeq).pop();
+        assert_eq!(last, Some(Err(ErrorKind::UnexpectedEof)));
+    }
+
+    #[test]
+    fn json_seq_documents_report_positions() {
+        let src = "\u{1e}[1]\n\u{1e}{\"a\":\n 1\n\u{1e}null\n";
+        let mut documents = JsonParser::new(src.chars()).documents(Framing::JsonSeq);
+        assert!(documents.next().unwrap().is_ok());
+        // a record cut short fails at the separator of the next one
+        let err = documents.next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedChar);
+        assert_eq!(err.found(), Some(RS));
+        assert_eq!((err.line(), err.column()), (4, 1));
+        assert_eq!(documents.next().unwrap().unwrap(), (Value::Null, 16..20));
+        assert!(documents.next().is_none());
+    }
+}
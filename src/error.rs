@@ -0,0 +1,237 @@
+use std::error;
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+
+/// The category of a [`JsonParserError`], stable enough to be matched on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The input ended in the middle of a value.
+    UnexpectedEof,
+    /// A character that cannot start or continue the current token.
+    UnexpectedChar,
+    /// A number that does not follow the json grammar, e.g. `01` or `1.`.
+    InvalidNumber,
+    /// A number too large to be represented, e.g. `1e400`.
+    NumberOutOfRange,
+    /// An unknown escape sequence or a malformed `\uXXXX` escape.
+    InvalidEscape,
+    /// A `\uXXXX` escape encoding a lone surrogate.
+    InvalidUnicode,
+    /// A control character (below U+0020) that was not escaped inside a string.
+    ControlCharacter,
+    /// Non whitespace content after the root value.
+    TrailingCharacters,
+    /// Arrays and objects nested deeper than the parser allows.
+    DepthLimit,
+    /// More input than the parser allows.
+    InputTooLong,
+    /// A string or object key longer than the parser allows.
+    StringTooLong,
+    /// A number literal longer than the parser allows.
+    NumberTooLong,
+    /// An array with more elements than the parser allows.
+    TooManyElements,
+    /// An object with more members than the parser allows.
+    TooManyMembers,
+    /// A key that appears more than once in the same object.
+    DuplicateKey,
+    /// Bytes that are not valid UTF-8.
+    InvalidUtf8,
+    /// Reading the input failed, see [`JsonParserError::io_error`].
+    Io,
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let desc = match self {
+            ErrorKind::UnexpectedEof => "unexpected end of input",
+            ErrorKind::UnexpectedChar => "unexpected character",
+            ErrorKind::InvalidNumber => "invalid number",
+            ErrorKind::NumberOutOfRange => "number out of range",
+            ErrorKind::InvalidEscape => "invalid escape sequence",
+            ErrorKind::InvalidUnicode => "invalid unicode code point",
+            ErrorKind::ControlCharacter => "unescaped control character",
+            ErrorKind::TrailingCharacters => "trailing characters",
+            ErrorKind::DepthLimit => "nesting too deep",
+            ErrorKind::InputTooLong => "input too long",
+            ErrorKind::StringTooLong => "string too long",
+            ErrorKind::NumberTooLong => "number too long",
+            ErrorKind::TooManyElements => "too many array elements",
+            ErrorKind::TooManyMembers => "too many object members",
+            ErrorKind::DuplicateKey => "duplicate object key",
+            ErrorKind::InvalidUtf8 => "invalid UTF-8",
+            ErrorKind::Io => "io error",
+        };
+        f.write_str(desc)
+    }
+}
+
+/// A location in the parsed input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Position {
+    pub(crate) line: u32,
+    pub(crate) col: u32,
+    pub(crate) offset: usize,
+}
+
+impl Position {
+    pub(crate) fn start() -> Self {
+        Self {
+            line: 1,
+            col: 1,
+            offset: 0,
+        }
+    }
+
+    /// Turns a location in input that was parsed on its own into the location in the
+    /// larger input it was taken from, `start` being where it starts in the latter.
+    pub(crate) fn relative_to(self, start: Position) -> Self {
+        Self {
+            line: self.line + start.line - 1,
+            col: if self.line == 1 {
+                self.col + start.col - 1
+            } else {
+                self.col
+            },
+            offset: self.offset + start.offset,
+        }
+    }
+
+    /// Line of the location, starting at 1.
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    /// Column of the location in characters, starting at 1.
+    pub fn column(&self) -> u32 {
+        self.col
+    }
+
+    /// Byte offset of the location from the start of the input.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct JsonParserError {
+    kind: ErrorKind,
+    msg: String,
+    expected: Option<&'static str>,
+    found: Option<char>,
+    pos: Position,
+    first_occurrence: Option<Position>,
+    io: Option<Arc<io::Error>>,
+}
+
+impl JsonParserError {
+    pub(crate) fn new(kind: ErrorKind, msg: impl Into<String>, pos: Position) -> Self {
+        Self {
+            kind,
+            msg: msg.into(),
+            expected: None,
+            found: None,
+            pos,
+            first_occurrence: None,
+            io: None,
+        }
+    }
+
+    pub(crate) fn with_expected(mut self, expected: &'static str) -> Self {
+        self.expected = Some(expected);
+        self
+    }
+
+    pub(crate) fn with_found(mut self, found: char) -> Self {
+        self.found = Some(found);
+        self
+    }
+
+    pub(crate) fn with_first_occurrence(mut self, pos: Position) -> Self {
+        self.first_occurrence = Some(pos);
+        self
+    }
+
+    pub(crate) fn with_io(mut self, err: io::Error) -> Self {
+        self.io = Some(Arc::new(err));
+        self
+    }
+
+    /// Moves the error, and the first occurrence of a duplicate key, with
+    /// [`Position::relative_to`].
+    pub(crate) fn relative_to(mut self, start: Position) -> Self {
+        self.pos = self.pos.relative_to(start);
+        self.first_occurrence = self.first_occurrence.map(|pos| pos.relative_to(start));
+        self
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Human readable description of the error, without position information.
+    pub fn message(&self) -> &str {
+        &self.msg
+    }
+
+    /// Description of the token the parser expected, e.g. `"',' or ']'"`, when the
+    /// error was caused by a specific token being missing.
+    pub fn expected(&self) -> Option<&'static str> {
+        self.expected
+    }
+
+    /// The character found at the error position, `None` at end of input.
+    pub fn found(&self) -> Option<char> {
+        self.found
+    }
+
+    /// Line of the error, starting at 1.
+    pub fn line(&self) -> u32 {
+        self.pos.line
+    }
+
+    /// Column of the error in characters, starting at 1.
+    pub fn column(&self) -> u32 {
+        self.pos.col
+    }
+
+    /// Byte offset of the error from the start of the input.
+    pub fn offset(&self) -> usize {
+        self.pos.offset
+    }
+
+    /// Position of the error, the same as [`line`](Self::line), [`column`](Self::column)
+    /// and [`offset`](Self::offset) together.
+    pub fn position(&self) -> Position {
+        self.pos
+    }
+
+    /// For [`ErrorKind::DuplicateKey`], the position of the first occurrence of the key.
+    /// The error itself points at the second one.
+    pub fn first_occurrence(&self) -> Option<Position> {
+        self.first_occurrence
+    }
+
+    /// For [`ErrorKind::Io`], the error returned by the reader.
+    pub fn io_error(&self) -> Option<&io::Error> {
+        self.io.as_deref()
+    }
+}
+
+impl fmt::Display for JsonParserError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Parse json error at line {} column {}: {}",
+            self.pos.line, self.pos.col, self.msg
+        )
+    }
+}
+
+impl error::Error for JsonParserError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        self.io.as_deref().map(|err| err as _)
+    }
+}
@@ -0,0 +1,508 @@
+use std::collections::HashMap;
+
+use super::{DuplicateKeys, JsonParser, JsonParserError, Number, Position, Source, duplicate_key};
+
+/// A token of a json document, as reported by [`EventParser`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    StartObject,
+    /// The key of an object member, followed by the events of its value.
+    Key(String),
+    EndObject,
+    StartArray,
+    EndArray,
+    String(String),
+    Number(Number),
+    Bool(bool),
+    Null,
+}
+
+/// A pull parser that reports a json value as a sequence of [`Event`]s instead of building
+/// a [`Value`](crate::Value), so it reads documents of any size in memory proportional to
+/// their nesting depth.
+///
+/// Created with [`JsonParser::events`], it parses with the same settings and fails with
+/// the same errors as [`JsonParser::parse`]. Every object member is reported as it is
+/// read, so of the [`DuplicateKeys`] policies only [`DuplicateKeys::Error`] has an effect.
+///
+/// ```
+/// use json::{Event, JsonParser};
+///
+/// let mut events = JsonParser::new(r#"{"skip": [1, 2], "keep": true}"#.chars()).events();
+/// assert_eq!(events.next_event().unwrap().unwrap().0, Event::StartObject);
+/// assert_eq!(events.next_event().unwrap().unwrap().0, Event::Key(String::from("skip")));
+/// events.skip_value().unwrap();
+/// assert_eq!(events.next_event().unwrap().unwrap().0, Event::Key(String::from("keep")));
+/// assert_eq!(events.next_event().unwrap().unwrap().0, Event::Bool(true));
+/// ```
+pub struct EventParser<T: Source> {
+    parser: JsonParser<T>,
+    reader: Reader,
+    /// The next event, once it has been read by [`peek_event`](Self::peek_event).
+    peeked: Option<Option<(Event, Position)>>,
+}
+
+/// What the parser reads next.
+#[derive(Clone, Copy)]
+enum State {
+    Value,
+    /// The first element of an array, or its end.
+    FirstElement,
+    /// The first member of an object, or its end.
+    FirstMember,
+    /// The separator after a value, or the end of the root value.
+    AfterValue,
+    Done,
+}
+
+/// Reads a value as events from the parser it is given, keeping track of where in the
+/// value the parser is.
+pub(crate) struct Reader {
+    /// The arrays and objects the next event is nested in.
+    stack: Vec<Container>,
+    state: State,
+    /// Whether strings and keys are only checked, leaving them empty in their events.
+    /// Keys are still read for [`DuplicateKeys::Error`].
+    skip_strings: bool,
+}
+
+struct Container {
+    is_object: bool,
+    /// Number of finished elements or members.
+    len: usize,
+    /// Position of every key, only tracked for [`DuplicateKeys::Error`].
+    keys: HashMap<String, Position>,
+}
+
+impl<T: Source> EventParser<T> {
+    pub(crate) fn new(parser: JsonParser<T>) -> Self {
+        Self {
+            parser,
+            reader: Reader::new(),
+            peeked: None,
+        }
+    }
+
+    /// Reads the next event and the position it starts at, or `None` once the root value
+    /// has ended. Input after the root value is left untouched.
+    pub fn next_event(&mut self) -> Result<Option<(Event, Position)>, JsonParserError> {
+        match self.peeked.take() {
+            Some(event) => Ok(event),
+            None => self.reader.read(&mut self.parser),
+        }
+    }
+
+    /// Returns the next event without consuming it.
+    pub fn peek_event(&mut self) -> Result<Option<&(Event, Position)>, JsonParserError> {
+        if self.peeked.is_none() {
+            self.peeked = Some(self.reader.read(&mut self.parser)?);
+        }
+        Ok(self.peeked.as_ref().and_then(Option::as_ref))
+    }
+
+    /// Skips the value that the next event starts, together with everything nested in
+    /// it. Does nothing when the next event is a key or ends a container. Strings and
+    /// keys are checked without being built.
+    pub fn skip_value(&mut self) -> Result<(), JsonParserError> {
+        let event = match self.peeked.take() {
+            Some(event) => event,
+            None if self.reader.at_key() => return Ok(()),
+            None => self.read_skipping()?,
+        };
+        let mut depth = match event {
+            Some((Event::StartArray | Event::StartObject, _)) => 1usize,
+            Some((Event::Key(_) | Event::EndArray | Event::EndObject, _)) | None => {
+                self.peeked = Some(event);
+                return Ok(());
+            }
+            Some(_) => return Ok(()),
+        };
+
+        while depth > 0 {
+            match self.read_skipping()? {
+                Some((Event::StartArray | Event::StartObject, _)) => depth += 1,
+                Some((Event::EndArray | Event::EndObject, _)) => depth -= 1,
+                Some(_) => {}
+                None => break,
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the next event without building the strings and keys in it.
+    fn read_skipping(&mut self) -> Result<Option<(Event, Position)>, JsonParserError> {
+        self.reader.skip_strings = true;
+        let event = self.reader.read(&mut self.parser);
+        self.reader.skip_strings = false;
+        event
+    }
+
+    /// Checks that only whitespace remains after the root value, like
+    /// [`JsonParser::end`].
+    pub fn end(&mut self) -> Result<(), JsonParserError> {
+        self.parser.end()
+    }
+}
+
+impl Reader {
+    pub(crate) fn new() -> Self {
+        Self {
+            stack: Vec::new(),
+            state: State::Value,
+            skip_strings: false,
+        }
+    }
+
+    /// Whether the next event is a key or the end of an object, or there is none.
+    fn at_key(&self) -> bool {
+        match self.state {
+            State::FirstMember | State::Done => true,
+            State::AfterValue => self
+                .stack
+                .last()
+                .is_some_and(|container| container.is_object),
+            State::Value | State::FirstElement => false,
+        }
+    }
+
+    /// Reads the next event of the value, or `None` once it has ended.
+    pub(crate) fn read<T: Source>(
+        &mut self,
+        parser: &mut JsonParser<T>,
+    ) -> Result<Option<(Event, Position)>, JsonParserError> {
+        match self.state {
+            State::Value => self.read_value(parser),
+            State::FirstElement => {
+                parser.skip_whitespace()?;
+                if let Some(']') = parser.peek()? {
+                    return self.read_end(parser, Event::EndArray);
+                }
+                parser.check_array_len(0)?;
+                self.read_value(parser)
+            }
+            State::FirstMember => {
+                parser.skip_whitespace()?;
+                if let Some('}') = parser.peek()? {
+                    return self.read_end(parser, Event::EndObject);
+                }
+                parser.check_object_len(0)?;
+                self.read_key(parser)
+            }
+            State::AfterValue => self.read_separator(parser),
+            State::Done => Ok(None),
+        }
+    }
+
+    fn read_value<T: Source>(
+        &mut self,
+        parser: &mut JsonParser<T>,
+    ) -> Result<Option<(Event, Position)>, JsonParserError> {
+        parser.skip_whitespace()?;
+        let pos = parser.pos;
+        let event = match parser.peek()? {
+            Some(ch @ ('[' | '{')) => {
+                parser.enter(self.stack.len())?;
+                let is_object = ch == '{';
+                self.stack.push(Container {
+                    is_object,
+                    len: 0,
+                    keys: HashMap::new(),
+                });
+                if is_object {
+                    self.state = State::FirstMember;
+                    return Ok(Some((Event::StartObject, pos)));
+                }
+                self.state = State::FirstElement;
+                return Ok(Some((Event::StartArray, pos)));
+            }
+            Some('t') => parser.read_word("true").map(|_| Event::Bool(true))?,
+            Some('f') => parser.read_word("false").map(|_| Event::Bool(false))?,
+            Some('n') => parser.read_word("null").map(|_| Event::Null)?,
+            Some('"') if self.skip_strings => {
+                parser.read_str(None)?;
+                Event::String(String::new())
+            }
+            Some('"') => Event::String(parser.parse_str()?),
+            Some(ch) if ch == '-' || ch.is_ascii_digit() => Event::Number(parser.read_number()?),
+            ch => return Err(parser.unexpected_value(ch)),
+        };
+        self.state = State::AfterValue;
+        Ok(Some((event, pos)))
+    }
+
+    fn read_key<T: Source>(
+        &mut self,
+        parser: &mut JsonParser<T>,
+    ) -> Result<Option<(Event, Position)>, JsonParserError> {
+        let duplicate_keys = parser.options.duplicate_keys;
+        let (key, pos) = if self.skip_strings && duplicate_keys != DuplicateKeys::Error {
+            (String::new(), parser.read_key(None)?)
+        } else {
+            parser.parse_key()?
+        };
+        if duplicate_keys == DuplicateKeys::Error {
+            let keys = &mut self
+                .stack
+                .last_mut()
+                .expect("keys should be read inside an object")
+                .keys;
+            if let Some(&first) = keys.get(&key) {
+                return Err(duplicate_key(&key, first, pos));
+            }
+            keys.insert(key.clone(), pos);
+        }
+        self.state = State::Value;
+        Ok(Some((Event::Key(key), pos)))
+    }
+
+    /// Reads the separator after a finished value, and what follows it.
+    fn read_separator<T: Source>(
+        &mut self,
+        parser: &mut JsonParser<T>,
+    ) -> Result<Option<(Event, Position)>, JsonParserError> {
+        let Some(container) = self.stack.last_mut() else {
+            self.state = State::Done;
+            return Ok(None);
+        };
+        container.len += 1;
+        let (is_object, len) = (container.is_object, container.len);
+
+        let (end, event) = if is_object {
+            ('}', Event::EndObject)
+        } else {
+            (']', Event::EndArray)
+        };
+        parser.skip_whitespace()?;
+        let pos = parser.pos;
+        if !parser.parse_separator(end)? {
+            self.stack.pop();
+            return Ok(Some((event, pos)));
+        }
+
+        if is_object {
+            parser.check_object_len(len)?;
+            self.read_key(parser)
+        } else {
+            parser.check_array_len(len)?;
+            self.read_value(parser)
+        }
+    }
+
+    /// Consumes the closing character of the innermost container.
+    fn read_end<T: Source>(
+        &mut self,
+        parser: &mut JsonParser<T>,
+        event: Event,
+    ) -> Result<Option<(Event, Position)>, JsonParserError> {
+        let pos = parser.pos;
+        parser.eat()?;
+        self.stack.pop();
+        self.state = State::AfterValue;
+        Ok(Some((event, pos)))
+    }
+}
+
+impl<T: Source> Iterator for EventParser<T> {
+    type Item = Result<(Event, Position), JsonParserError>;
+
+    /// Ends after the root value or after the first error.
+    fn next(&mut self) -> Option<Self::Item> {
+        let event = self.next_event();
+        if event.is_err() {
+            self.reader.state = State::Done;
+        }
+        event.transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Array, ErrorKind, Map, Value};
+
+    fn events(src: &str) -> Vec<(Event, u32, u32)> {
+        JsonParser::new(src.chars())
+            .events()
+            .map(|event| {
+                let (event, pos) = event.unwrap();
+                (event, pos.line(), pos.column())
+            })
+            .collect()
+    }
+
+    /// Builds the value reported by `events`, to compare it with [`JsonParser::parse`].
+    fn build<T: Source>(events: &mut EventParser<T>) -> Result<Value, JsonParserError> {
+        let mut stack: Vec<(Value, Option<String>)> = Vec::new();
+        while let Some((event, _)) = events.next_event()? {
+            let value = match event {
+                Event::StartArray => {
+                    stack.push((Value::Array(Array::new()), None));
+                    continue;
+                }
+                Event::StartObject => {
+                    stack.push((Value::Object(Map::new()), None));
+                    continue;
+                }
+                Event::Key(key) => {
+                    stack.last_mut().unwrap().1 = Some(key);
+                    continue;
+                }
+                Event::EndArray | Event::EndObject => stack.pop().unwrap().0,
+                Event::String(s) => Value::String(s),
+                Event::Number(n) => Value::Number(n),
+                Event::Bool(b) => Value::Bool(b),
+                Event::Null => Value::Null,
+            };
+            match stack.last_mut() {
+                None => return Ok(value),
+                Some((Value::Array(values), _)) => values.push(value),
+                Some((Value::Object(members), key)) => {
+                    members.insert(key.take().unwrap(), value);
+                }
+                Some(_) => unreachable!("only containers are pushed"),
+            }
+        }
+        unreachable!("events should end after the root value")
+    }
+
+    #[test]
+    fn events_works() {
+        let src = "{\"a\": [1, \"x\", {}],\n \"b\": null, \"c\": [true, false, []]}";
+        let expected = [
+            (Event::StartObject, 1, 1),
+            (Event::Key(String::from("a")), 1, 2),
+            (Event::StartArray, 1, 7),
+            (Event::Number(Number::from(1)), 1, 8),
+            (Event::String(String::from("x")), 1, 11),
+            (Event::StartObject, 1, 16),
+            (Event::EndObject, 1, 17),
+            (Event::EndArray, 1, 18),
+            (Event::Key(String::from("b")), 2, 2),
+            (Event::Null, 2, 7),
+            (Event::Key(String::from("c")), 2, 13),
+            (Event::StartArray, 2, 18),
+            (Event::Bool(true), 2, 19),
+            (Event::Bool(false), 2, 25),
+            (Event::StartArray, 2, 32),
+            (Event::EndArray, 2, 33),
+            (Event::EndArray, 2, 34),
+            (Event::EndObject, 2, 35),
+        ];
+        assert_eq!(events(src), expected);
+        assert_eq!(events(" 12 "), [(Event::Number(Number::from(12)), 1, 2)]);
+    }
+
+    #[test]
+    fn events_match_parse() {
+        let inputs = [
+            "null",
+            "[1, [2, [3]], {\"a\": {\"b\": []}}]",
+            "[1 2]",
+            "[1,]",
+            "{\"a\" 1}",
+            "{\"a\": 1,}",
+            "{1: 2}",
+            "[\"\\x\"]",
+            "[01]",
+            "[1e400]",
+            "[[[[",
+            "[tru]",
+            "{\"a\": 1, \"a\": 2}",
+            "[1, 2, 3, 4]",
+            "{\"a\": 1, \"b\": 2, \"c\": 3}",
+            "[[1, 2], {\"a\": [3, 4, 5]}]",
+            "",
+        ];
+        for src in inputs {
+            for (max_depth, max_len) in [(128, usize::MAX), (2, 2), (1, 0), (3, 3)] {
+                let parser = || {
+                    JsonParser::new(src.chars())
+                        .max_depth(max_depth)
+                        .max_array_len(max_len)
+                        .max_object_len(max_len)
+                        .max_input_len(20)
+                        .duplicate_keys(DuplicateKeys::Error)
+                };
+                let expected = parser().parse();
+                let actual = build(&mut parser().events());
+                match (actual, expected) {
+                    (Ok(actual), Ok(expected)) => assert_eq!(actual, expected, "{src}"),
+                    (Err(actual), Err(expected)) => {
+                        assert_eq!(actual.kind(), expected.kind(), "{src}");
+                        assert_eq!(actual.message(), expected.message(), "{src}");
+                        assert_eq!(actual.position(), expected.position(), "{src}");
+                    }
+                    (actual, expected) => panic!("{src}: {actual:?} != {expected:?}"),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn events_skip_value_works() {
+        let src = r#"[{"big": [1, [2, {"x": 3}]], "small": 4}, "after"]"#;
+        let mut events = JsonParser::new(src.chars()).events();
+        assert_eq!(events.next_event().unwrap().unwrap().0, Event::StartArray);
+        assert_eq!(events.next_event().unwrap().unwrap().0, Event::StartObject);
+        assert_eq!(
+            events.next_event().unwrap().unwrap().0,
+            Event::Key(String::from("big"))
+        );
+        events.skip_value().unwrap();
+        let (event, pos) = events.next_event().unwrap().unwrap();
+        assert_eq!(event, Event::Key(String::from("small")));
+        assert_eq!(pos.column(), 30);
+
+        assert_eq!(
+            events.peek_event().unwrap().unwrap().0,
+            Event::Number(Number::from(4))
+        );
+        events.skip_value().unwrap();
+        // nothing to skip before the end of a container
+        events.skip_value().unwrap();
+        assert_eq!(events.next_event().unwrap().unwrap().0, Event::EndObject);
+        // array elements are skipped one at a time
+        events.skip_value().unwrap();
+        assert_eq!(events.next_event().unwrap().unwrap().0, Event::EndArray);
+        assert!(events.next_event().unwrap().is_none());
+        events.end().unwrap();
+    }
+
+    #[test]
+    fn events_skip_value_checks_strings() {
+        let mut events = JsonParser::new(r#"["skip \u00e9", 1]"#.chars()).events();
+        assert_eq!(events.next_event().unwrap().unwrap().0, Event::StartArray);
+        events.skip_value().unwrap();
+        let (event, pos) = events.next_event().unwrap().unwrap();
+        assert_eq!(event, Event::Number(Number::from(1)));
+        assert_eq!(pos.column(), 17);
+
+        for (src, kind) in [
+            (r#"[["a\x"]]"#, ErrorKind::InvalidEscape),
+            (r#"[{"k\u12": 1}]"#, ErrorKind::InvalidEscape),
+            (r#"["abcd"]"#, ErrorKind::StringTooLong),
+            (r#"[{"abcd": 1}]"#, ErrorKind::StringTooLong),
+            (r#"[{"a": 1, "a": 2}]"#, ErrorKind::DuplicateKey),
+        ] {
+            let mut events = JsonParser::new(src.chars())
+                .max_string_len(3)
+                .duplicate_keys(DuplicateKeys::Error)
+                .events();
+            assert_eq!(events.next_event().unwrap().unwrap().0, Event::StartArray);
+            let err = events.skip_value().unwrap_err();
+            assert_eq!(err.kind(), kind, "{src}");
+        }
+    }
+
+    #[test]
+    fn events_stop_after_errors() {
+        let src = "[1, x, 2]";
+        let mut events = JsonParser::from_reader(src.as_bytes()).events();
+        assert!(matches!(events.next(), Some(Ok((Event::StartArray, _)))));
+        assert!(matches!(events.next(), Some(Ok((Event::Number(_), _)))));
+        let err = events.next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedChar);
+        assert_eq!(err.column(), 5);
+        assert!(events.next().is_none());
+    }
+}
@@ -1,167 +1,577 @@
-use std::collections::BTreeMap;
+use std::fmt::{self, Write as _};
+use std::io::{self, Write as _};
+use std::{mem, slice, vec};
+
+use super::map::{self, Map};
+use super::{Number, Value};
+
+/// The line break written between lines of pretty printed output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineEnding {
+    /// `\n`
+    #[default]
+    Lf,
+    /// `\r\n`
+    CrLf,
+}
 
-use super::Value;
+impl LineEnding {
+    fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
 
+/// How a [`Formatter`] writes NaN and infinite numbers, which json cannot represent.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NonFinite {
+    /// Fails with an error.
+    Error,
+    /// Writes `null`, like `JSON.stringify` does.
+    #[default]
+    Null,
+    /// Writes the strings `"NaN"`, `"Infinity"` and `"-Infinity"`.
+    String,
+}
+
+/// Writes [`Value`]s as json text.
+///
+/// [`Formatter::new`] writes compact output and [`Formatter::standard`] pretty prints with
+/// two spaces of indentation. Both can be adjusted with the chained setters, e.g.
+///
+/// ```
+/// use json::format::{Formatter, LineEnding};
+///
+/// let formatter = Formatter::standard()
+///     .indent("\t")
+///     .line_ending(LineEnding::CrLf)
+///     .trailing_newline(true);
+/// let value: json::Value = "[1]".parse().unwrap();
+/// assert_eq!(formatter.format(&value).unwrap(), "[\r\n\t1\r\n]\r\n");
+/// ```
 #[derive(Clone, Debug)]
 pub struct Formatter {
-    spacing: u8,
+    indent: Option<String>,
+    line_ending: LineEnding,
+    space_after_colon: bool,
+    space_after_comma: bool,
+    trailing_newline: bool,
+    max_width: Option<usize>,
+    non_finite: NonFinite,
+    exponent_notation: bool,
+    ensure_ascii: bool,
+    sort_keys: bool,
 }
 
 impl Formatter {
+    /// A formatter writing everything on one line, without any whitespace.
     pub fn new() -> Self {
-        Self { spacing: 0 }
+        Self {
+            indent: None,
+            line_ending: LineEnding::Lf,
+            space_after_colon: false,
+            space_after_comma: false,
+            trailing_newline: false,
+            max_width: None,
+            non_finite: NonFinite::Null,
+            exponent_notation: false,
+            ensure_ascii: false,
+            sort_keys: false,
+        }
     }
 
+    /// A formatter writing each array element and object member on its own line,
+    /// indented by two spaces per level, like `JSON.stringify(value, null, 2)`.
     pub fn standard() -> Self {
-        Self { spacing: 2 }
+        Self::new().indent("  ").space_after_colon(true)
     }
 
-    pub fn format(&self, value: &Value) -> String {
-        let mut buf = String::new();
-        self.format_in(&mut buf, value);
-        buf
+    /// Writes each array element and object member on its own line, indented by `indent`
+    /// once per nesting level, e.g. `"    "` or `"\t"`. An empty `indent` still breaks
+    /// lines but does not indent them.
+    pub fn indent(mut self, indent: &str) -> Self {
+        self.indent = Some(indent.to_owned());
+        self
     }
 
-    fn format_in(&self, buf: &mut String, value: &Value) {
-        match value {
-            Value::Null => buf.push_str("null"),
-            Value::Bool(true) => buf.push_str("true"),
-            Value::Bool(false) => buf.push_str("false"),
-            Value::String(s) => self.format_str(buf, s),
-            Value::Number(n) => buf.push_str(&n.to_string()),
-            Value::Array(arr) => self.format_arr(buf, arr),
-            Value::Object(map) => self.format_object(buf, map),
-        }
+    /// Writes everything on one line again, undoing [`indent`](Self::indent).
+    pub fn compact(mut self) -> Self {
+        self.indent = None;
+        self
     }
 
-    fn format_str(&self, buf: &mut String, s: &str) {
-        buf.push('"');
-        buf.push_str(s);
-        buf.push('"');
+    /// The line break used when indenting and for the trailing newline.
+    pub fn line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
     }
 
-    fn format_arr(&self, buf: &mut String, arr: &[Value]) {
-        if self.spacing > 0 {
-            self.format_arr_spaced(buf, arr);
-        } else {
-            self.format_arr_unspaced(buf, arr);
+    /// Writes a space after the `:` that follows an object key.
+    pub fn space_after_colon(mut self, space_after_colon: bool) -> Self {
+        self.space_after_colon = space_after_colon;
+        self
+    }
+
+    /// Writes a space after the `,` between elements and members. Only applies to
+    /// compact output, when indenting the comma is always followed by a line break.
+    pub fn space_after_comma(mut self, space_after_comma: bool) -> Self {
+        self.space_after_comma = space_after_comma;
+        self
+    }
+
+    /// Ends the output with a line break, as most editors do for files.
+    pub fn trailing_newline(mut self, trailing_newline: bool) -> Self {
+        self.trailing_newline = trailing_newline;
+        self
+    }
+
+    /// When indenting, keeps an array or object on a single line if it fits within
+    /// `max_width` characters, counting everything already on the line, and breaks it up
+    /// otherwise. Containers kept on one line follow the compact spacing options, e.g.
+    /// `[1, 2]` with [`space_after_comma`](Self::space_after_comma).
+    pub fn max_width(mut self, max_width: usize) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+
+    /// How NaN and infinite numbers are written, `null` by default.
+    pub fn non_finite(mut self, non_finite: NonFinite) -> Self {
+        self.non_finite = non_finite;
+        self
+    }
+
+    /// When enabled, floats with a magnitude of at least 1e21 or below 1e-6 are written
+    /// in exponent notation, e.g. `1e300` instead of a 1 followed by 300 zeros, using the
+    /// same thresholds as JavaScript.
+    pub fn exponent_notation(mut self, exponent_notation: bool) -> Self {
+        self.exponent_notation = exponent_notation;
+        self
+    }
+
+    /// When enabled, every non-ASCII code point in strings and object keys is written
+    /// as a `\uXXXX` escape (using a surrogate pair outside the basic multilingual plane).
+    pub fn ensure_ascii(mut self, ensure_ascii: bool) -> Self {
+        self.ensure_ascii = ensure_ascii;
+        self
+    }
+
+    /// When enabled, object members are written sorted by key instead of in the order
+    /// of the [`Map`], giving a canonical output regardless of how the map was built.
+    pub fn sort_keys(mut self, sort_keys: bool) -> Self {
+        self.sort_keys = sort_keys;
+        self
+    }
+
+    /// Formats `value` into a new string. Fails only if `value` contains a non-finite
+    /// number and the formatter uses [`NonFinite::Error`].
+    ///
+    /// Every finite float is written so that parsing the output gives back the same
+    /// value.
+    pub fn format(&self, value: &Value) -> Result<String, fmt::Error> {
+        let mut buf = String::new();
+        self.format_to(&mut buf, value)?;
+        Ok(buf)
+    }
+
+    /// Writes `value` into `out` as it is formatted, without building the whole output
+    /// in memory first. Fails if `out` does, or if `value` contains a non-finite number
+    /// and the formatter uses [`NonFinite::Error`].
+    pub fn format_to<W: fmt::Write>(&self, out: &mut W, value: &Value) -> fmt::Result {
+        let mut out = Sink {
+            inner: out,
+            column: self.max_width.map(|_| 0),
+        };
+        self.format_in(&mut out, value)?;
+        if self.trailing_newline {
+            out.write_str(self.line_ending.as_str())?;
         }
+        Ok(())
     }
 
-    fn format_arr_unspaced(&self, buf: &mut String, arr: &[Value]) {
-        buf.push('[');
+    /// Writes `value` into `writer` as it is formatted. Writes are buffered, so `writer`
+    /// does not need to be buffered itself. A non-finite number with [`NonFinite::Error`]
+    /// fails with [`io::ErrorKind::InvalidData`].
+    pub fn write_to<W: io::Write>(&self, writer: W, value: &Value) -> io::Result<()> {
+        let mut writer = IoSink {
+            inner: io::BufWriter::new(writer),
+            error: None,
+        };
+        match self.format_to(&mut writer, value) {
+            Ok(()) => writer.inner.flush(),
+            Err(fmt::Error) => Err(writer.error.unwrap_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "non-finite numbers cannot be written as json",
+                )
+            })),
+        }
+    }
 
-        for (idx, v) in arr.iter().enumerate() {
-            self.format_in(buf, v);
-            if idx != arr.len() - 1 {
-                buf.push(',');
+    /// Writes `value` into `out`. Nested arrays and objects are walked with an explicit
+    /// stack, so deeply nested values cannot overflow the thread stack.
+    fn format_in<W: fmt::Write>(&self, out: &mut Sink<W>, value: &Value) -> fmt::Result {
+        let mut stack = Vec::<Frame>::new();
+        let inline = self.inline_formatter();
+        let mut next = Some(value);
+        loop {
+            if let (Some(inline), Some(value)) = (&inline, next)
+                && value.is_container()
+                && self.fits(value, self.remaining_width(out))
+            {
+                inline.format_in(out, value)?;
+                next = None;
+            }
+
+            match next.take() {
+                Some(Value::Array(arr)) if arr.is_empty() => out.write_str("[]")?,
+                Some(Value::Object(map)) if map.is_empty() => out.write_str("{}")?,
+                Some(Value::Array(arr)) => {
+                    out.write_char('[')?;
+                    stack.push(Frame::Array(arr.iter(), false));
+                }
+                Some(Value::Object(map)) => {
+                    out.write_char('{')?;
+                    stack.push(Frame::Object(self.members(map), false));
+                }
+                Some(scalar) => self.format_scalar(out, scalar)?,
+                None => {}
+            }
+
+            let depth = stack.len();
+            match stack.last_mut() {
+                None => break,
+                Some(Frame::Array(iter, started)) => match iter.next() {
+                    Some(v) => {
+                        self.format_separator(out, mem::replace(started, true), depth)?;
+                        next = Some(v);
+                    }
+                    None => {
+                        stack.pop();
+                        self.format_close(out, ']', depth - 1)?;
+                    }
+                },
+                Some(Frame::Object(iter, started)) => match iter.next() {
+                    Some((k, v)) => {
+                        self.format_separator(out, mem::replace(started, true), depth)?;
+                        self.format_str(out, k)?;
+                        out.write_str(if self.space_after_colon { ": " } else { ":" })?;
+                        next = Some(v);
+                    }
+                    None => {
+                        stack.pop();
+                        self.format_close(out, '}', depth - 1)?;
+                    }
+                },
             }
         }
+        Ok(())
+    }
 
-        buf.push(']');
+    /// The formatter used for containers that fit on one line, `None` unless both
+    /// indenting and a max width are set.
+    fn inline_formatter(&self) -> Option<Formatter> {
+        self.indent.as_ref()?;
+        self.max_width?;
+        Some(Self {
+            indent: None,
+            max_width: None,
+            ..self.clone()
+        })
     }
 
-    fn format_arr_spaced(&self, buf: &mut String, arr: &[Value]) {
-        buf.push_str("[\n");
-        for _ in 0..self.spacing {
-            buf.push(' ');
-        }
+    /// Characters left on the current line of `out` before reaching the max width.
+    fn remaining_width<W>(&self, out: &Sink<W>) -> usize {
+        self.max_width
+            .unwrap_or(usize::MAX)
+            .saturating_sub(out.column.unwrap_or(0))
+    }
 
-        for (idx, v) in arr.iter().enumerate() {
-            self.format_in(buf, v);
-            if idx != arr.len() - 1 {
-                buf.push_str(",\n");
-                for _ in 0..self.spacing {
-                    buf.push(' ');
+    /// Whether `value` written on one line takes at most `width` characters. The walk
+    /// stops as soon as the width is exceeded, so checking a large value is cheap.
+    fn fits(&self, value: &Value, width: usize) -> bool {
+        let separator = if self.space_after_comma { 2 } else { 1 };
+        let colon = if self.space_after_colon { 2 } else { 1 };
+        let mut len = 0;
+        let mut scratch = String::new();
+        let mut stack = vec![value];
+        while let Some(value) = stack.pop() {
+            match value {
+                Value::Array(arr) => {
+                    // every element takes at least one character
+                    if arr.len() > width {
+                        return false;
+                    }
+                    len += 2 + arr.len().saturating_sub(1) * separator;
+                    stack.extend(arr);
+                }
+                Value::Object(map) => {
+                    if map.len() > width {
+                        return false;
+                    }
+                    len += 2 + map.len().saturating_sub(1) * separator + map.len() * colon;
+                    for (k, v) in map {
+                        len += self.str_width(&mut scratch, k, width);
+                        stack.push(v);
+                    }
                 }
+                Value::String(s) => len += self.str_width(&mut scratch, s, width),
+                scalar => {
+                    scratch.clear();
+                    // a non-finite number with `NonFinite::Error`, which the caller
+                    // reports when it writes the value
+                    if self.format_scalar(&mut scratch, scalar).is_err() {
+                        return false;
+                    }
+                    len += scratch.len();
+                }
+            }
+            if len > width {
+                return false;
             }
         }
+        true
+    }
+
+    /// Number of characters `s` takes once quoted and escaped, or more than `width`
+    /// without escaping it when it clearly does not fit.
+    fn str_width(&self, scratch: &mut String, s: &str, width: usize) -> usize {
+        // a char is at most four bytes and escaping never makes a string shorter
+        if s.len() / 4 > width {
+            return s.len() / 4;
+        }
+        scratch.clear();
+        self.format_str(scratch, s)
+            .expect("writing to a string should not fail");
+        scratch.chars().count()
+    }
 
-        buf.push(']');
+    fn format_scalar<W: fmt::Write>(&self, out: &mut W, value: &Value) -> fmt::Result {
+        match value {
+            Value::Null => out.write_str("null"),
+            Value::Bool(true) => out.write_str("true"),
+            Value::Bool(false) => out.write_str("false"),
+            Value::String(s) => self.format_str(out, s),
+            Value::Number(n) => match n.as_float() {
+                Some(f) => self.format_float(out, f),
+                None => write!(out, "{n}"),
+            },
+            Value::Array(_) | Value::Object(_) => {
+                unreachable!("containers are formatted by format_in")
+            }
+        }
     }
 
-    fn format_object(&self, buf: &mut String, obj: &BTreeMap<String, Value>) {
-        if self.spacing > 0 {
-            self.format_object_spaced(buf, obj);
+    fn format_float<W: fmt::Write>(&self, out: &mut W, f: f64) -> fmt::Result {
+        if !f.is_finite() {
+            let name = match f {
+                f64::INFINITY => "\"Infinity\"",
+                f64::NEG_INFINITY => "\"-Infinity\"",
+                _ => "\"NaN\"",
+            };
+            return match self.non_finite {
+                NonFinite::Error => Err(fmt::Error),
+                NonFinite::Null => out.write_str("null"),
+                NonFinite::String => out.write_str(name),
+            };
+        }
+        let magnitude = f.abs();
+        if self.exponent_notation && magnitude != 0.0 && !(1e-6..1e21).contains(&magnitude) {
+            // the shortest digits that read back as `f`, so this round trips as well
+            write!(out, "{f:e}")
         } else {
-            self.format_object_unspaced(buf, obj);
+            write!(out, "{}", Number::from_f64(f))
         }
     }
 
-    fn format_object_unspaced(&self, buf: &mut String, obj: &BTreeMap<String, Value>) {
-        buf.push('{');
+    fn format_str<W: fmt::Write>(&self, out: &mut W, s: &str) -> fmt::Result {
+        out.write_char('"')?;
+        // runs of characters that need no escaping are written in one go
+        let mut run_start = 0;
+        for (i, ch) in s.char_indices() {
+            let escape = match ch {
+                '"' => "\\\"",
+                '\\' => "\\\\",
+                '\n' => "\\n",
+                '\r' => "\\r",
+                '\t' => "\\t",
+                '\u{08}' => "\\b",
+                '\u{0C}' => "\\f",
+                ch if ch < '\u{20}' || (self.ensure_ascii && !ch.is_ascii()) => "",
+                _ => continue,
+            };
+            out.write_str(&s[run_start..i])?;
+            run_start = i + ch.len_utf8();
+            if escape.is_empty() {
+                self.format_unicode_escape(out, ch)?;
+            } else {
+                out.write_str(escape)?;
+            }
+        }
+        out.write_str(&s[run_start..])?;
+        out.write_char('"')
+    }
 
-        for (idx, (k, v)) in obj.iter().enumerate() {
-            buf.push('"');
-            buf.push_str(k);
-            buf.push_str(r#"":"#);
+    fn format_unicode_escape<W: fmt::Write>(&self, out: &mut W, ch: char) -> fmt::Result {
+        let mut units = [0; 2];
+        for unit in ch.encode_utf16(&mut units) {
+            write!(out, "\\u{unit:04x}")?;
+        }
+        Ok(())
+    }
 
-            self.format_in(buf, v);
-            if idx != obj.len() - 1 {
-                buf.push(',');
+    fn members<'a>(&self, map: &'a Map) -> Members<'a> {
+        if self.sort_keys {
+            let mut members: Vec<_> = map.iter().collect();
+            members.sort_unstable_by_key(|(k, _)| *k);
+            Members::Sorted(members.into_iter())
+        } else {
+            Members::Map(map.iter())
+        }
+    }
+
+    /// Writes what goes before an element of a container nested `depth` levels deep: a
+    /// comma unless it is the first element, then a line break and indentation when
+    /// indenting or the optional space after the comma otherwise.
+    fn format_separator<W: fmt::Write>(
+        &self,
+        out: &mut W,
+        started: bool,
+        depth: usize,
+    ) -> fmt::Result {
+        if started {
+            out.write_char(',')?;
+            if self.indent.is_none() && self.space_after_comma {
+                out.write_char(' ')?;
             }
         }
+        self.format_newline(out, depth)
+    }
 
-        buf.push('}');
+    /// Closes a non-empty container, putting the bracket on its own line when indenting.
+    fn format_close<W: fmt::Write>(&self, out: &mut W, ch: char, depth: usize) -> fmt::Result {
+        self.format_newline(out, depth)?;
+        out.write_char(ch)
     }
 
-    fn format_object_spaced(&self, buf: &mut String, obj: &BTreeMap<String, Value>) {
-        buf.push_str("{\n");
-        for _ in 0..self.spacing {
-            buf.push(' ');
+    fn format_newline<W: fmt::Write>(&self, out: &mut W, depth: usize) -> fmt::Result {
+        if let Some(indent) = &self.indent {
+            out.write_str(self.line_ending.as_str())?;
+            for _ in 0..depth {
+                out.write_str(indent)?;
+            }
         }
+        Ok(())
+    }
+}
 
-        for (idx, (k, v)) in obj.iter().enumerate() {
-            buf.push('"');
-            buf.push_str(k);
-            buf.push_str(r#"": "#);
+/// Forwards writes to the underlying sink, keeping track of the column the next
+/// character will be written at when a [`Formatter::max_width`] is set.
+struct Sink<'a, W> {
+    inner: &'a mut W,
+    /// `None` when there is no max width, so plain writes skip counting characters.
+    column: Option<usize>,
+}
 
-            self.format_in(buf, v);
-            if idx != obj.len() - 1 {
-                buf.push_str(",\n");
-                for _ in 0..self.spacing {
-                    buf.push(' ');
-                }
+impl<W: fmt::Write> fmt::Write for Sink<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if let Some(column) = &mut self.column {
+            match s.rfind('\n') {
+                Some(i) => *column = s[i + 1..].chars().count(),
+                None => *column += s.chars().count(),
             }
         }
+        self.inner.write_str(s)
+    }
+}
+
+/// Adapts an [`io::Write`] to [`fmt::Write`], keeping the io error that `fmt::Error`
+/// cannot carry.
+struct IoSink<W: io::Write> {
+    inner: io::BufWriter<W>,
+    error: Option<io::Error>,
+}
 
-        buf.push_str("\n}");
+impl<W: io::Write> fmt::Write for IoSink<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|err| {
+            self.error = Some(err);
+            fmt::Error
+        })
+    }
+}
+
+/// An array or object whose elements are still being written. The flag records
+/// whether an element has been written already, i.e. whether a separator is needed.
+enum Frame<'a> {
+    Array(slice::Iter<'a, Value>, bool),
+    Object(Members<'a>, bool),
+}
+
+enum Members<'a> {
+    Map(map::Iter<'a>),
+    Sorted(vec::IntoIter<(&'a String, &'a Value)>),
+}
+
+impl<'a> Iterator for Members<'a> {
+    type Item = (&'a String, &'a Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Members::Map(iter) => iter.next(),
+            Members::Sorted(iter) => iter.next(),
+        }
+    }
+}
+
+impl Default for Formatter {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Array;
 
     #[test]
     fn formatter_without_spacing_works() {
         let formatter = Formatter::new();
         let value = Value::Null;
-        assert_eq!(formatter.format(&value), "null");
+        assert_eq!(formatter.format(&value).unwrap(), "null");
 
         let value = Value::Bool(true);
-        assert_eq!(formatter.format(&value), "true");
+        assert_eq!(formatter.format(&value).unwrap(), "true");
 
         let value = Value::Bool(false);
-        assert_eq!(formatter.format(&value), "false");
+        assert_eq!(formatter.format(&value).unwrap(), "false");
 
         let value = Value::String(String::from("test"));
-        assert_eq!(formatter.format(&value), r#""test""#);
+        assert_eq!(formatter.format(&value).unwrap(), r#""test""#);
 
-        let value = Value::Number(12.345);
-        assert_eq!(formatter.format(&value), "12.345");
+        let value = Value::Number(Number::from(12.345));
+        assert_eq!(formatter.format(&value).unwrap(), "12.345");
 
-        let arr = vec![Value::Null, Value::Bool(false), Value::Number(1.23)];
-        let value = Value::Array(arr);
-        assert_eq!(formatter.format(&value), "[null,false,1.23]");
+        let arr = vec![
+            Value::Null,
+            Value::Bool(false),
+            Value::Number(Number::from(1.23)),
+        ];
+        let value = Value::Array(arr.into());
+        assert_eq!(formatter.format(&value).unwrap(), "[null,false,1.23]");
 
-        let mut map = BTreeMap::new();
+        let mut map = Map::new();
         map.insert(String::from("alive"), Value::Bool(true));
-        map.insert(String::from("times_cried"), Value::Number(123.0));
+        map.insert(
+            String::from("times_cried"),
+            Value::Number(Number::from(123)),
+        );
         map.insert(String::from("wife"), Value::Null);
         let value = Value::Object(map);
         assert_eq!(
-            formatter.format(&value),
+            formatter.format(&value).unwrap(),
             r#"{"alive":true,"times_cried":123,"wife":null}"#
         );
     }
@@ -170,32 +580,425 @@ mod tests {
     fn formattter_with_spacing_works() {
         let formatter = Formatter::standard();
         let value = Value::Null;
-        assert_eq!(formatter.format(&value), "null");
+        assert_eq!(formatter.format(&value).unwrap(), "null");
 
         let value = Value::Bool(true);
-        assert_eq!(formatter.format(&value), "true");
+        assert_eq!(formatter.format(&value).unwrap(), "true");
 
         let value = Value::Bool(false);
-        assert_eq!(formatter.format(&value), "false");
+        assert_eq!(formatter.format(&value).unwrap(), "false");
 
         let value = Value::String(String::from("test"));
-        assert_eq!(formatter.format(&value), r#""test""#);
+        assert_eq!(formatter.format(&value).unwrap(), r#""test""#);
 
-        let value = Value::Number(12.345);
-        assert_eq!(formatter.format(&value), "12.345");
+        let value = Value::Number(Number::from(12.345));
+        assert_eq!(formatter.format(&value).unwrap(), "12.345");
 
-        let arr = vec![Value::Null, Value::Bool(false), Value::Number(1.23)];
-        let value = Value::Array(arr);
-        assert_eq!(formatter.format(&value), "[\n  null,\n  false,\n  1.23]");
+        let arr = vec![
+            Value::Null,
+            Value::Bool(false),
+            Value::Number(Number::from(1.23)),
+        ];
+        let value = Value::Array(arr.into());
+        assert_eq!(
+            formatter.format(&value).unwrap(),
+            "[\n  null,\n  false,\n  1.23\n]"
+        );
 
-        let mut map = BTreeMap::new();
+        let mut map = Map::new();
         map.insert(String::from("alive"), Value::Bool(true));
-        map.insert(String::from("times_cried"), Value::Number(123.0));
+        map.insert(
+            String::from("times_cried"),
+            Value::Number(Number::from(123)),
+        );
         map.insert(String::from("wife"), Value::Null);
         let value = Value::Object(map);
         assert_eq!(
-            formatter.format(&value),
+            formatter.format(&value).unwrap(),
             "{\n  \"alive\": true,\n  \"times_cried\": 123,\n  \"wife\": null\n}"
         );
     }
+
+    #[test]
+    fn formatter_indents_nested_values() {
+        let formatter = Formatter::standard();
+        let src = r#"{"a":[1,[2,{"b":null}],{}],"c":{"d":[],"e":{"f":"g"}}}"#;
+        let value: Value = src.parse().unwrap();
+        let expected = r#"{
+  "a": [
+    1,
+    [
+      2,
+      {
+        "b": null
+      }
+    ],
+    {}
+  ],
+  "c": {
+    "d": [],
+    "e": {
+      "f": "g"
+    }
+  }
+}"#;
+        assert_eq!(formatter.format(&value).unwrap(), expected);
+
+        let value: Value = "[[[]]]".parse().unwrap();
+        assert_eq!(formatter.format(&value).unwrap(), "[\n  [\n    []\n  ]\n]");
+    }
+
+    #[test]
+    fn formatter_writes_empty_containers() {
+        for formatter in [Formatter::new(), Formatter::standard()] {
+            assert_eq!(formatter.format(&Value::Array(Array::new())).unwrap(), "[]");
+            assert_eq!(formatter.format(&Value::Object(Map::new())).unwrap(), "{}");
+        }
+        let value: Value = r#"[{},[]]"#.parse().unwrap();
+        assert_eq!(Formatter::new().format(&value).unwrap(), "[{},[]]");
+        assert_eq!(
+            Formatter::standard().format(&value).unwrap(),
+            "[\n  {},\n  []\n]"
+        );
+    }
+
+    #[test]
+    fn formatter_options_works() {
+        let value: Value = r#"{"a":[1,2],"b":{}}"#.parse().unwrap();
+
+        let formatter = Formatter::new()
+            .space_after_colon(true)
+            .space_after_comma(true);
+        assert_eq!(
+            formatter.format(&value).unwrap(),
+            r#"{"a": [1, 2], "b": {}}"#
+        );
+
+        let formatter = Formatter::standard().indent("\t").space_after_comma(true);
+        assert_eq!(
+            formatter.format(&value).unwrap(),
+            "{\n\t\"a\": [\n\t\t1,\n\t\t2\n\t],\n\t\"b\": {}\n}"
+        );
+
+        let formatter = Formatter::new()
+            .indent("    ")
+            .line_ending(LineEnding::CrLf)
+            .trailing_newline(true);
+        assert_eq!(
+            formatter.format(&value).unwrap(),
+            "{\r\n    \"a\":[\r\n        1,\r\n        2\r\n    ],\r\n    \"b\":{}\r\n}\r\n"
+        );
+
+        let formatter = Formatter::standard().indent("");
+        assert_eq!(
+            formatter.format(&value).unwrap(),
+            "{\n\"a\": [\n1,\n2\n],\n\"b\": {}\n}"
+        );
+
+        let formatter = Formatter::standard().compact().trailing_newline(true);
+        assert_eq!(
+            formatter.format(&value).unwrap(),
+            "{\"a\": [1,2],\"b\": {}}\n"
+        );
+        assert_eq!(formatter.format(&Value::Null).unwrap(), "null\n");
+    }
+
+    #[test]
+    fn formatter_max_width_works() {
+        let src = r#"{"name":"route","points":[[1.5,2],[3,4.25]],"tags":["a","b"],"meta":{"id":1,"nested":{"deep":[true,false,null]}}}"#;
+        let value: Value = src.parse().unwrap();
+
+        let formatter = Formatter::standard().space_after_comma(true).max_width(40);
+        let expected = r#"{
+  "meta": {
+    "id": 1,
+    "nested": {
+      "deep": [true, false, null]
+    }
+  },
+  "name": "route",
+  "points": [[1.5, 2], [3, 4.25]],
+  "tags": ["a", "b"]
+}"#;
+        assert_eq!(formatter.sort_keys(true).format(&value).unwrap(), expected);
+
+        // the whole value fits, so it stays on one line
+        let formatter = Formatter::standard().max_width(usize::MAX);
+        let out = formatter.format(&value).unwrap();
+        assert!(!out.contains('\n'), "{out}");
+        assert_eq!(out.parse::<Value>().unwrap(), value);
+
+        // nothing fits, which is the same as not setting a max width
+        let formatter = Formatter::standard().max_width(0);
+        assert_eq!(
+            formatter.format(&value).unwrap(),
+            Formatter::standard().format(&value).unwrap()
+        );
+
+        // the max width has no effect on compact output
+        let formatter = Formatter::new().max_width(0);
+        assert_eq!(
+            formatter.format(&value).unwrap(),
+            Formatter::new().format(&value).unwrap()
+        );
+    }
+
+    #[test]
+    fn formatter_max_width_counts_characters() {
+        let value: Value = r#"["éé", "\n"]"#.parse().unwrap();
+        // `["éé","\n"]` is 11 characters but 13 bytes
+        let formatter = Formatter::standard().max_width(11);
+        assert_eq!(formatter.format(&value).unwrap(), r#"["éé","\n"]"#);
+        let formatter = Formatter::standard().max_width(10);
+        assert_eq!(
+            formatter.format(&value).unwrap(),
+            "[\n  \"éé\",\n  \"\\n\"\n]"
+        );
+    }
+
+    #[test]
+    fn formatter_writes_to_sinks() {
+        let src = r#"{"a":[1,"x\ny",{"b":null}],"c":"é"}"#;
+        let value: Value = src.parse().unwrap();
+        let formatters = [
+            Formatter::new(),
+            Formatter::standard().trailing_newline(true),
+            Formatter::standard().max_width(20).ensure_ascii(true),
+        ];
+        for formatter in formatters {
+            let expected = formatter.format(&value).unwrap();
+
+            let mut out = Vec::new();
+            formatter.write_to(&mut out, &value).unwrap();
+            assert_eq!(String::from_utf8(out).unwrap(), expected);
+
+            let mut out = String::from("prefix ");
+            formatter.format_to(&mut out, &value).unwrap();
+            assert_eq!(out, format!("prefix {expected}"));
+        }
+        assert_eq!(
+            value.to_string(),
+            Formatter::standard().format(&value).unwrap()
+        );
+    }
+
+    #[test]
+    fn formatter_write_to_reports_io_errors() {
+        struct Full;
+
+        impl io::Write for Full {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::StorageFull, "disk full"))
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        // large enough to overflow the write buffer before the end
+        let value = Value::Array(vec![Value::Null; 10_000].into());
+        let err = Formatter::new().write_to(Full, &value).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::StorageFull);
+
+        let err = Formatter::new().write_to(Full, &Value::Null).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::StorageFull);
+    }
+
+    #[test]
+    fn formatter_non_finite_works() {
+        let value = Value::Array(
+            vec![
+                Value::Number(Number::from(f64::NAN)),
+                Value::Number(Number::from(f64::INFINITY)),
+                Value::Number(Number::from(f64::NEG_INFINITY)),
+            ]
+            .into(),
+        );
+        assert_eq!(Formatter::new().format(&value).unwrap(), "[null,null,null]");
+        let formatter = Formatter::new().non_finite(NonFinite::String);
+        assert_eq!(
+            formatter.format(&value).unwrap(),
+            r#"["NaN","Infinity","-Infinity"]"#
+        );
+
+        let formatter = Formatter::new().non_finite(NonFinite::Error);
+        assert_eq!(formatter.format(&value), Err(fmt::Error));
+        let mut out = String::new();
+        assert_eq!(formatter.format_to(&mut out, &value), Err(fmt::Error));
+        let err = formatter.write_to(io::sink(), &value).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(value.to_string(), "[\n  null,\n  null,\n  null\n]");
+    }
+
+    #[test]
+    fn formatter_max_width_reports_non_finite() {
+        let nan = Value::Number(Number::from(f64::NAN));
+        let value = Value::Array(vec![Value::Array(vec![nan.clone()].into()), nan].into());
+        let formatter = Formatter::standard()
+            .max_width(80)
+            .non_finite(NonFinite::Error);
+        assert_eq!(formatter.format(&value), Err(fmt::Error));
+        let mut out = String::new();
+        assert_eq!(formatter.format_to(&mut out, &value), Err(fmt::Error));
+        let err = formatter.write_to(io::sink(), &value).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let formatter = formatter.non_finite(NonFinite::Null);
+        assert_eq!(formatter.format(&value).unwrap(), "[[null],null]");
+    }
+
+    #[test]
+    fn formatter_exponent_notation_works() {
+        let cases = [
+            (1e22, "1e22", "10000000000000000000000.0"),
+            (-1.5e21, "-1.5e21", "-1500000000000000000000.0"),
+            (1e21, "1e21", "1000000000000000000000.0"),
+            (1e20, "100000000000000000000.0", "100000000000000000000.0"),
+            (2.5e-7, "2.5e-7", "0.00000025"),
+            (1e-6, "0.000001", "0.000001"),
+            (0.0, "0.0", "0.0"),
+            (-0.0, "-0.0", "-0.0"),
+            (123.456, "123.456", "123.456"),
+        ];
+        for (f, exponent, plain) in cases {
+            let value = Value::Number(Number::from(f));
+            let formatter = Formatter::new().exponent_notation(true);
+            assert_eq!(formatter.format(&value).unwrap(), exponent);
+            assert_eq!(Formatter::new().format(&value).unwrap(), plain);
+        }
+        // 1 followed by 300 digits and a fraction
+        let value = Value::Number(Number::from(1e300));
+        assert_eq!(Formatter::new().format(&value).unwrap().len(), 303);
+        assert_eq!(
+            Formatter::new()
+                .exponent_notation(true)
+                .format(&value)
+                .unwrap(),
+            "1e300"
+        );
+
+        let value = Value::Number(Number::from(5u64));
+        assert_eq!(
+            Formatter::new()
+                .exponent_notation(true)
+                .format(&value)
+                .unwrap(),
+            "5"
+        );
+    }
+
+    #[test]
+    fn formatter_floats_round_trip() {
+        let mut floats = vec![
+            0.0,
+            -0.0,
+            1.0,
+            -1.0,
+            0.1,
+            1.0 / 3.0,
+            f64::MAX,
+            f64::MIN,
+            f64::MIN_POSITIVE,
+            f64::EPSILON,
+            5e-324,
+            9007199254740993.0,
+            1.8446744073709552e19,
+        ];
+        // a simple xorshift walks through the bit patterns of arbitrary doubles
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        while floats.len() < 10_000 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let f = f64::from_bits(state);
+            if f.is_finite() {
+                floats.push(f);
+            }
+        }
+        for exponent_notation in [false, true] {
+            let formatter = Formatter::new().exponent_notation(exponent_notation);
+            for &f in &floats {
+                let value = Value::Number(Number::from(f));
+                let out = formatter.format(&value).unwrap();
+                let parsed: Value = out.parse().unwrap();
+                assert_eq!(parsed, value, "{out}");
+                assert_eq!(parsed.as_f64().unwrap().to_bits(), f.to_bits(), "{out}");
+            }
+        }
+    }
+
+    #[test]
+    fn formatter_escapes_strings() {
+        let formatter = Formatter::new();
+        let value = Value::String(String::from("a\"b\\c/d\n\r\t\u{08}\u{0C}\u{01}\u{1F}é"));
+        assert_eq!(
+            formatter.format(&value).unwrap(),
+            r#""a\"b\\c/d\n\r\t\b\f\u0001\u001fé""#
+        );
+
+        let mut map = Map::new();
+        map.insert(String::from("quo\"te"), Value::Null);
+        let value = Value::Object(map);
+        assert_eq!(formatter.format(&value).unwrap(), r#"{"quo\"te":null}"#);
+    }
+
+    #[test]
+    fn formatter_ensure_ascii_works() {
+        let formatter = Formatter::new().ensure_ascii(true);
+        let value = Value::String(String::from("aé€😀"));
+        assert_eq!(
+            formatter.format(&value).unwrap(),
+            r#""a\u00e9\u20ac\ud83d\ude00""#
+        );
+
+        let mut map = Map::new();
+        map.insert(String::from("clé"), Value::String(String::from("\n")));
+        let value = Value::Object(map);
+        assert_eq!(formatter.format(&value).unwrap(), r#"{"cl\u00e9":"\n"}"#);
+    }
+
+    #[test]
+    fn formatter_output_round_trips() {
+        let src = "a\"\\\n\u{0}é😀";
+        for formatter in [Formatter::new(), Formatter::new().ensure_ascii(true)] {
+            let out = formatter.format(&Value::String(String::from(src))).unwrap();
+            let mut parser = crate::JsonParser::new(out.chars());
+            assert_eq!(parser.parse().unwrap(), Value::String(String::from(src)));
+        }
+    }
+
+    #[test]
+    fn formatter_keeps_integer_precision() {
+        let formatter = Formatter::new();
+        for src in [
+            "9007199254740993",
+            "18446744073709551615",
+            "-9223372036854775808",
+        ] {
+            let value = crate::JsonParser::new(src.chars()).parse().unwrap();
+            assert_eq!(formatter.format(&value).unwrap(), src);
+        }
+    }
+
+    #[test]
+    fn formatter_writes_raw_numbers_verbatim() {
+        let src = r#"[1.10,2.5E-3,1e400,-0.0000000000000000000000001]"#;
+        let value = crate::JsonParser::new(src.chars())
+            .arbitrary_precision(true)
+            .parse()
+            .unwrap();
+        assert_eq!(Formatter::new().format(&value).unwrap(), src);
+    }
+
+    #[test]
+    fn formatter_sort_keys_works() {
+        let src = r#"{"b":1,"a":{"d":2,"c":3},"C":4}"#;
+        let value: Value = src.parse().unwrap();
+        let out = Formatter::new().sort_keys(true).format(&value).unwrap();
+        assert_eq!(out, r#"{"C":4,"a":{"c":3,"d":2},"b":1}"#);
+
+        #[cfg(feature = "preserve_order")]
+        assert_eq!(Formatter::new().format(&value).unwrap(), src);
+    }
 }
@@ -0,0 +1,931 @@
+use std::str;
+
+use super::{DuplicateKeys, ErrorKind, JsonParserError, Options, Position, SliceParser, Value};
+
+/// What [`IncrementalParser::next_value`] found in the input fed so far.
+#[derive(Debug, PartialEq)]
+pub enum Progress {
+    /// The next complete value.
+    Value(Value),
+    /// The input fed so far ends before the next value does.
+    NeedMoreInput,
+    /// The input is [finished](IncrementalParser::finish) and every value was returned,
+    /// or parsing stopped after an error.
+    Done,
+}
+
+/// A parser fed the input in chunks as it arrives, e.g. from a socket, that returns
+/// values as soon as they are complete. Chunks may split the input anywhere, including
+/// inside strings, escapes, numbers and UTF-8 characters.
+///
+/// The input is a stream of values, optionally separated by whitespace. Every value is
+/// parsed once it is complete, like a [`SliceParser`] would, so values and errors are the
+/// same, with positions in the whole input. Bytes are checked as they are fed, so input
+/// that cannot be valid fails without waiting for the rest of the value, and so do the
+/// limits, except [`max_object_len`](Self::max_object_len) and
+/// [`duplicate_keys`](Self::duplicate_keys), which depend on the keys and are checked
+/// once the value is complete.
+///
+/// ```
+/// use json::{IncrementalParser, Progress, Value};
+///
+/// let mut parser = IncrementalParser::new();
+/// parser.feed(b"{\"a\": \"x\\");
+/// assert_eq!(parser.next_value().unwrap(), Progress::NeedMoreInput);
+/// parser.feed(b"ny\"} 1");
+/// assert!(matches!(parser.next_value().unwrap(), Progress::Value(Value::Object(_))));
+/// // the number may go on in the next chunk
+/// assert_eq!(parser.next_value().unwrap(), Progress::NeedMoreInput);
+/// parser.finish();
+/// assert!(matches!(parser.next_value().unwrap(), Progress::Value(Value::Number(_))));
+/// assert_eq!(parser.next_value().unwrap(), Progress::Done);
+/// ```
+pub struct IncrementalParser {
+    buf: Vec<u8>,
+    /// Where the next value starts in `buf`, the bytes before it have been parsed.
+    start: usize,
+    /// End of the bytes of the next value scanned so far.
+    scanned: usize,
+    /// Position of `start` in the whole input.
+    pos: Position,
+    scan: Scan,
+    options: Options,
+    finished: bool,
+    failed: bool,
+}
+
+/// The next value scanned so far: the containers open around the scan position, what
+/// the innermost one expects next and the token in progress. Bytes that cannot go on
+/// with the value are caught as soon as they are fed.
+struct Scan {
+    /// Whether the value has started, anything scanned before it is whitespace.
+    started: bool,
+    stack: Vec<Container>,
+    expect: Expect,
+    token: Option<Token>,
+}
+
+struct Container {
+    object: bool,
+    /// Values in the array, or members in the object, scanned so far.
+    len: usize,
+}
+
+#[derive(Clone, Copy)]
+enum Expect {
+    /// A value, or the end of the array right after it starts.
+    Value {
+        first: bool,
+    },
+    /// A key, or the end of the object right after it starts.
+    Key {
+        first: bool,
+    },
+    Colon,
+    /// A comma or the end of the container.
+    Separator,
+    /// The delimiter that ends a number or a literal at the top level.
+    Delimiter,
+}
+
+enum Token {
+    /// A string, `len` bytes long unescaped so far.
+    Str {
+        key: bool,
+        len: usize,
+        escape: Escape,
+    },
+    /// A number, `len` characters long so far.
+    Number {
+        state: NumberState,
+        len: usize,
+    },
+    Literal {
+        word: &'static [u8],
+        matched: usize,
+    },
+}
+
+#[derive(Clone, Copy)]
+enum Escape {
+    None,
+    /// Right after a backslash.
+    Start,
+    /// In the hex digits of a unicode escape, the second one of a surrogate pair when
+    /// `low`.
+    Hex {
+        low: bool,
+        digits: u8,
+        code: u32,
+    },
+    /// After a leading surrogate, waiting for the `\u` of the trailing one.
+    Trailing {
+        backslash: bool,
+    },
+}
+
+/// The part of a number the last character belongs to.
+#[derive(Clone, Copy)]
+enum NumberState {
+    Minus,
+    Zero,
+    Int,
+    Dot,
+    Frac,
+    Exp,
+    ExpSign,
+    ExpInt,
+}
+
+impl NumberState {
+    /// The state after `b`, `None` when the number cannot go on with it.
+    fn next(self, b: u8) -> Option<Self> {
+        let next = match (self, b) {
+            (Self::Minus, b'0') => Self::Zero,
+            (Self::Minus | Self::Int, b'0'..=b'9') => Self::Int,
+            (Self::Zero | Self::Int, b'.') => Self::Dot,
+            (Self::Dot | Self::Frac, b'0'..=b'9') => Self::Frac,
+            (Self::Zero | Self::Int | Self::Frac, b'e' | b'E') => Self::Exp,
+            (Self::Exp, b'+' | b'-') => Self::ExpSign,
+            (Self::Exp | Self::ExpSign | Self::ExpInt, b'0'..=b'9') => Self::ExpInt,
+            _ => return None,
+        };
+        Some(next)
+    }
+
+    /// Whether the number can end here.
+    fn is_complete(self) -> bool {
+        matches!(self, Self::Zero | Self::Int | Self::Frac | Self::ExpInt)
+    }
+}
+
+/// Where the scan of the input fed so far got to.
+enum Scanned {
+    /// The next value ends at this offset.
+    Value(usize),
+    /// The value cannot go on with the character at the scan position.
+    Invalid,
+    Incomplete,
+}
+
+/// What a character does to the value being scanned.
+enum Step {
+    /// It is part of the value, which goes on.
+    Next,
+    /// It ends the token in progress without being part of it, so it is scanned again.
+    Again,
+    /// It is the last character of the value.
+    End,
+    /// The value ends right before it.
+    EndBefore,
+    Invalid,
+}
+
+impl Scan {
+    fn new() -> Self {
+        Self {
+            started: false,
+            stack: Vec::new(),
+            expect: Expect::Value { first: false },
+            token: None,
+        }
+    }
+
+    /// Scans the next character, starting with byte `b` and `len` bytes long, 0 when it
+    /// is not valid UTF-8.
+    fn step(&mut self, b: u8, len: usize, options: &Options) -> Step {
+        match self.token {
+            Some(Token::Str { .. }) => return self.string(b, len, options),
+            Some(Token::Number { .. }) => return self.number(b, options),
+            Some(Token::Literal { word, matched }) => {
+                if word[matched] != b {
+                    return Step::Invalid;
+                }
+                self.token = Some(Token::Literal {
+                    word,
+                    matched: matched + 1,
+                });
+                if matched + 1 == word.len() {
+                    self.token = None;
+                    self.close(true);
+                }
+                return Step::Next;
+            }
+            None => {}
+        }
+
+        let whitespace = matches!(b, b' ' | b'\t' | b'\n' | b'\r');
+        match self.expect {
+            Expect::Delimiter => {
+                if whitespace || matches!(b, b'"' | b'[' | b'{' | b']' | b'}') {
+                    Step::EndBefore
+                } else {
+                    Step::Invalid
+                }
+            }
+            _ if whitespace => Step::Next,
+            Expect::Value { first } => match self.stack.last() {
+                Some(array) if !array.object && first && b == b']' => self.close_container(),
+                Some(array) if !array.object && array.len >= options.max_array_len => Step::Invalid,
+                _ => self.start_value(b, options),
+            },
+            // the member limit depends on which keys are duplicates, so it is left to the
+            // parser
+            Expect::Key { first } => match b {
+                b'}' if first => self.close_container(),
+                b'"' => {
+                    self.token = Some(Token::Str {
+                        key: true,
+                        len: 0,
+                        escape: Escape::None,
+                    });
+                    Step::Next
+                }
+                _ => Step::Invalid,
+            },
+            Expect::Colon if b == b':' => {
+                self.expect = Expect::Value { first: false };
+                Step::Next
+            }
+            Expect::Colon => Step::Invalid,
+            Expect::Separator => {
+                let object = self.stack.last().is_some_and(|container| container.object);
+                match b {
+                    b',' if object => self.expect = Expect::Key { first: false },
+                    b',' => self.expect = Expect::Value { first: false },
+                    b'}' if object => return self.close_container(),
+                    b']' if !object => return self.close_container(),
+                    _ => return Step::Invalid,
+                }
+                Step::Next
+            }
+        }
+    }
+
+    fn start_value(&mut self, b: u8, options: &Options) -> Step {
+        self.started = true;
+        let token = match b {
+            b'[' | b'{' => {
+                if self.stack.len() >= options.max_depth {
+                    return Step::Invalid;
+                }
+                let object = b == b'{';
+                self.stack.push(Container { object, len: 0 });
+                self.expect = if object {
+                    Expect::Key { first: true }
+                } else {
+                    Expect::Value { first: true }
+                };
+                return Step::Next;
+            }
+            b'"' => Token::Str {
+                key: false,
+                len: 0,
+                escape: Escape::None,
+            },
+            b'-' | b'0'..=b'9' => {
+                if options.max_number_len == 0 {
+                    return Step::Invalid;
+                }
+                let state = match b {
+                    b'-' => NumberState::Minus,
+                    b'0' => NumberState::Zero,
+                    _ => NumberState::Int,
+                };
+                Token::Number { state, len: 1 }
+            }
+            b't' => Token::Literal {
+                word: b"true",
+                matched: 1,
+            },
+            b'f' => Token::Literal {
+                word: b"false",
+                matched: 1,
+            },
+            b'n' => Token::Literal {
+                word: b"null",
+                matched: 1,
+            },
+            _ => return Step::Invalid,
+        };
+        self.token = Some(token);
+        Step::Next
+    }
+
+    fn string(&mut self, b: u8, len: usize, options: &Options) -> Step {
+        let Some(Token::Str {
+            key,
+            len: str_len,
+            escape,
+        }) = &mut self.token
+        else {
+            unreachable!("token should be a string");
+        };
+        match *escape {
+            Escape::None => match b {
+                b'"' => {
+                    let key = *key;
+                    self.token = None;
+                    if key {
+                        self.expect = Expect::Colon;
+                        return Step::Next;
+                    }
+                    return self.close(false);
+                }
+                b'\\' => *escape = Escape::Start,
+                ..0x20 => return Step::Invalid,
+                _ if len == 0 => return Step::Invalid,
+                _ => *str_len += len,
+            },
+            Escape::Start => match b {
+                b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't' => {
+                    *escape = Escape::None;
+                    *str_len += 1;
+                }
+                b'u' => {
+                    *escape = Escape::Hex {
+                        low: false,
+                        digits: 0,
+                        code: 0,
+                    };
+                }
+                _ => return Step::Invalid,
+            },
+            Escape::Hex { low, digits, code } => {
+                let Some(digit) = char::from(b).to_digit(16) else {
+                    return Step::Invalid;
+                };
+                let code = code * 16 + digit;
+                if digits < 3 {
+                    *escape = Escape::Hex {
+                        low,
+                        digits: digits + 1,
+                        code,
+                    };
+                    return Step::Next;
+                }
+                *escape = Escape::None;
+                match (low, code) {
+                    (false, 0xD800..=0xDBFF) => {
+                        *escape = Escape::Trailing { backslash: false };
+                    }
+                    (true, 0xDC00..=0xDFFF) => *str_len += 4,
+                    (true, _) | (false, 0xDC00..=0xDFFF) => return Step::Invalid,
+                    (false, _) => {
+                        let ch = char::from_u32(code)
+                            .expect("non surrogate code point should be a valid char");
+                        *str_len += ch.len_utf8();
+                    }
+                }
+            }
+            Escape::Trailing { backslash: false } if b == b'\\' => {
+                *escape = Escape::Trailing { backslash: true };
+            }
+            Escape::Trailing { backslash: true } if b == b'u' => {
+                *escape = Escape::Hex {
+                    low: true,
+                    digits: 0,
+                    code: 0,
+                };
+            }
+            Escape::Trailing { .. } => return Step::Invalid,
+        }
+
+        if *str_len > options.max_string_len {
+            return Step::Invalid;
+        }
+        Step::Next
+    }
+
+    fn number(&mut self, b: u8, options: &Options) -> Step {
+        let Some(Token::Number { state, len }) = &mut self.token else {
+            unreachable!("token should be a number");
+        };
+        match state.next(b) {
+            Some(next) => {
+                *state = next;
+                *len += 1;
+                if *len > options.max_number_len {
+                    return Step::Invalid;
+                }
+                Step::Next
+            }
+            None if state.is_complete() => {
+                self.token = None;
+                self.close(true);
+                Step::Again
+            }
+            None => Step::Invalid,
+        }
+    }
+
+    fn close_container(&mut self) -> Step {
+        self.stack.pop();
+        self.close(false)
+    }
+
+    /// Moves past a complete value. At the top level, it is the whole value unless it is
+    /// a number or a literal, which needs a delimiter after it.
+    fn close(&mut self, scalar: bool) -> Step {
+        match self.stack.last_mut() {
+            Some(container) => {
+                container.len += 1;
+                self.expect = Expect::Separator;
+                Step::Next
+            }
+            None if scalar => {
+                self.expect = Expect::Delimiter;
+                Step::Next
+            }
+            None => Step::End,
+        }
+    }
+}
+
+impl IncrementalParser {
+    pub fn new() -> Self {
+        Self {
+            buf: Vec::new(),
+            start: 0,
+            scanned: 0,
+            pos: Position::start(),
+            scan: Scan::new(),
+            options: Options::default(),
+            finished: false,
+            failed: false,
+        }
+    }
+
+    /// Same as [`JsonParser::max_depth`](crate::JsonParser::max_depth).
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.options.max_depth = max_depth;
+        self
+    }
+
+    /// Same as [`JsonParser::max_input_len`](crate::JsonParser::max_input_len), counting
+    /// all the input fed.
+    pub fn max_input_len(mut self, max_input_len: usize) -> Self {
+        self.options.max_input_len = max_input_len;
+        self
+    }
+
+    /// Same as [`JsonParser::max_string_len`](crate::JsonParser::max_string_len).
+    pub fn max_string_len(mut self, max_string_len: usize) -> Self {
+        self.options.max_string_len = max_string_len;
+        self
+    }
+
+    /// Same as [`JsonParser::max_number_len`](crate::JsonParser::max_number_len).
+    pub fn max_number_len(mut self, max_number_len: usize) -> Self {
+        self.options.max_number_len = max_number_len;
+        self
+    }
+
+    /// Same as [`JsonParser::max_array_len`](crate::JsonParser::max_array_len).
+    pub fn max_array_len(mut self, max_array_len: usize) -> Self {
+        self.options.max_array_len = max_array_len;
+        self
+    }
+
+    /// Same as [`JsonParser::max_object_len`](crate::JsonParser::max_object_len).
+    pub fn max_object_len(mut self, max_object_len: usize) -> Self {
+        self.options.max_object_len = max_object_len;
+        self
+    }
+
+    /// Same as [`JsonParser::duplicate_keys`](crate::JsonParser::duplicate_keys).
+    pub fn duplicate_keys(mut self, duplicate_keys: DuplicateKeys) -> Self {
+        self.options.duplicate_keys = duplicate_keys;
+        self
+    }
+
+    /// Same as [`JsonParser::arbitrary_precision`](crate::JsonParser::arbitrary_precision).
+    pub fn arbitrary_precision(mut self, arbitrary_precision: bool) -> Self {
+        self.options.arbitrary_precision = arbitrary_precision;
+        self
+    }
+
+    /// Byte offset in the whole input of the first byte not parsed yet.
+    pub fn offset(&self) -> usize {
+        self.pos.offset
+    }
+
+    /// Appends the next chunk of input.
+    pub fn feed(&mut self, chunk: &[u8]) {
+        // drop the parsed bytes once they are at least half of the buffer, so moving the
+        // rest costs no more than parsing them did
+        if self.start > 0 && self.start * 2 >= self.buf.len() {
+            self.buf.drain(..self.start);
+            self.scanned -= self.start;
+            self.start = 0;
+        }
+        self.buf.extend_from_slice(chunk);
+    }
+
+    /// Marks the end of the input, so a value left incomplete is an error instead of
+    /// waiting for more input.
+    pub fn finish(&mut self) {
+        self.finished = true;
+    }
+
+    /// Parses the next value if the input fed so far holds all of it. After an error,
+    /// parsing stops and [`Progress::Done`] is returned from then on.
+    pub fn next_value(&mut self) -> Result<Progress, JsonParserError> {
+        if self.failed {
+            return Ok(Progress::Done);
+        }
+        let progress = self.advance();
+        self.failed = progress.is_err();
+        progress
+    }
+
+    fn advance(&mut self) -> Result<Progress, JsonParserError> {
+        let end = match self.scan() {
+            Scanned::Value(end) => Some(end),
+            // the parser reports the error, it is within the input fed so far
+            Scanned::Invalid => None,
+            Scanned::Incomplete if !self.scan.started => {
+                // only whitespace is left
+                self.consume(self.scanned);
+                if self.finished {
+                    return Ok(Progress::Done);
+                }
+                return Ok(Progress::NeedMoreInput);
+            }
+            // the rest of the input has to be the value, or is too long to be one
+            Scanned::Incomplete if self.finished || self.too_long() => None,
+            Scanned::Incomplete => return Ok(Progress::NeedMoreInput),
+        };
+
+        let mut options = self.options.clone();
+        options.max_input_len = options.max_input_len.saturating_sub(self.pos.offset);
+        // parse past the end, so the value ends and fails at the same byte as it would in
+        // the whole input
+        let mut parser = SliceParser::with_options(&self.buf[self.start..], options);
+        let value = parser.parse().and_then(|value| match end {
+            Some(_) => Ok(value),
+            // e.g. `1,2`, a number followed by something else than a delimiter
+            None => parser.end().map(|()| value),
+        });
+        let value = value.map_err(|err| self.locate(err))?;
+        self.consume(end.unwrap_or(self.buf.len()));
+        self.scan = Scan::new();
+        Ok(Progress::Value(value))
+    }
+
+    /// Scans the bytes fed since the last call, up to the end of the next value or the
+    /// first byte that cannot be part of it.
+    fn scan(&mut self) -> Scanned {
+        while let Some(&b) = self.buf.get(self.scanned) {
+            let mut len = 1;
+            if !b.is_ascii() {
+                // wait for the rest of the character, the error is not the same when it
+                // is cut short
+                match char_len(&self.buf[self.scanned..]) {
+                    Some(char_len) => len = char_len,
+                    None => return Scanned::Incomplete,
+                }
+            }
+            match self.scan.step(b, len, &self.options) {
+                Step::Next => self.scanned += len,
+                Step::Again => {}
+                Step::End => {
+                    self.scanned += len;
+                    return Scanned::Value(self.scanned);
+                }
+                Step::EndBefore => return Scanned::Value(self.scanned),
+                Step::Invalid => return Scanned::Invalid,
+            }
+        }
+        Scanned::Incomplete
+    }
+
+    /// Whether the incomplete value already goes past the maximum input length.
+    fn too_long(&self) -> bool {
+        self.pos.offset + (self.buf.len() - self.start) > self.options.max_input_len
+    }
+
+    /// Moves past the bytes up to `end`.
+    fn consume(&mut self, end: usize) {
+        let parser = SliceParser::new(&self.buf[self.start..end]);
+        self.pos = parser.position(end - self.start).relative_to(self.pos);
+        self.start = end;
+    }
+
+    /// Moves an error from the parser of a single value to its place in the whole input.
+    fn locate(&self, err: JsonParserError) -> JsonParserError {
+        let err = if err.kind() == ErrorKind::InputTooLong {
+            let msg = format!(
+                "input is longer than the maximum of {} bytes",
+                self.options.max_input_len
+            );
+            JsonParserError::new(ErrorKind::InputTooLong, msg, err.position())
+        } else {
+            err
+        };
+        err.relative_to(self.pos)
+    }
+}
+
+/// Length of the character `bytes` start with, 0 when it is not valid UTF-8, or `None`
+/// when `bytes` end before that can be told.
+fn char_len(bytes: &[u8]) -> Option<usize> {
+    let bytes = &bytes[..bytes.len().min(4)];
+    let valid = match str::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(err) if err.valid_up_to() > 0 => {
+            str::from_utf8(&bytes[..err.valid_up_to()]).expect("prefix should be valid UTF-8")
+        }
+        Err(err) => return err.error_len().map(|_| 0),
+    };
+    valid.chars().next().map(char::len_utf8)
+}
+
+impl Default for IncrementalParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{JsonParser, Number, from_slice};
+
+    type Outcome = Result<Value, (ErrorKind, Position)>;
+
+    /// Feeds `chunks`, collecting every value up to the first error.
+    fn values(mut parser: IncrementalParser, chunks: &[&[u8]]) -> Vec<Outcome> {
+        let mut outcomes = Vec::new();
+        for (i, chunk) in chunks.iter().enumerate() {
+            parser.feed(chunk);
+            if i == chunks.len() - 1 {
+                parser.finish();
+            }
+            loop {
+                match parser.next_value() {
+                    Ok(Progress::Value(value)) => outcomes.push(Ok(value)),
+                    Ok(Progress::NeedMoreInput | Progress::Done) => break,
+                    Err(err) => {
+                        outcomes.push(Err((err.kind(), err.position())));
+                        return outcomes;
+                    }
+                }
+            }
+        }
+        outcomes
+    }
+
+    #[test]
+    fn incremental_parser_works() {
+        let src = "{\"a\": [1, -2.5e3, \"x\\\"y\"]} \"\\u00e9é\"\n[true,null]{}12 false\r\n";
+        let expected: Vec<Outcome> = JsonParser::new(src.chars())
+            .documents(crate::Framing::Concatenated)
+            .map(|document| Ok(document.unwrap().0))
+            .collect();
+        assert_eq!(expected.len(), 6);
+
+        let bytes: Vec<&[u8]> = src.as_bytes().chunks(1).collect();
+        assert_eq!(values(IncrementalParser::new(), &bytes), expected);
+        for size in [2, 3, 7, 64] {
+            let chunks: Vec<&[u8]> = src.as_bytes().chunks(size).collect();
+            assert_eq!(values(IncrementalParser::new(), &chunks), expected);
+        }
+    }
+
+    #[test]
+    fn incremental_parser_resumes_mid_token() {
+        let chunks: [&[u8]; 8] = [
+            b"[\"a\\",
+            b"u00",
+            b"e9\xc3",
+            b"\xa9\", 1",
+            b"2",
+            b".",
+            b"5e",
+            b"1]",
+        ];
+        let mut parser = IncrementalParser::new();
+        for chunk in chunks {
+            assert_eq!(parser.next_value().unwrap(), Progress::NeedMoreInput);
+            parser.feed(chunk);
+        }
+        let value = Value::Array(
+            vec![
+                Value::String(String::from("aéé")),
+                Value::Number(Number::from(125.0)),
+            ]
+            .into(),
+        );
+        assert_eq!(parser.next_value().unwrap(), Progress::Value(value));
+    }
+
+    #[test]
+    fn incremental_parser_waits_for_numbers() {
+        let mut parser = IncrementalParser::new();
+        parser.feed(b" 12");
+        assert_eq!(parser.next_value().unwrap(), Progress::NeedMoreInput);
+        parser.feed(b"3 ");
+        let value = parser.next_value().unwrap();
+        assert_eq!(value, Progress::Value(Value::Number(Number::from(123))));
+        assert_eq!(parser.offset(), 4);
+        assert_eq!(parser.next_value().unwrap(), Progress::NeedMoreInput);
+        parser.finish();
+        assert_eq!(parser.next_value().unwrap(), Progress::Done);
+    }
+
+    #[test]
+    fn incremental_parser_matches_from_slice() {
+        let corpus: &[&[u8]] = &[
+            b"{\"a\": {\"b\": [1, 2.5, \"\\ud83d\\ude00\"]}, \"c\": null}",
+            b"\"\xc3\xa9\\n\"",
+            b"-0.5e-3",
+            b"true",
+            b"[1,]",
+            b"{\"a\" 1}",
+            b"{\"a\": 1, \"a\": 2}",
+            b"\"\\x\"",
+            b"\"\xff\"",
+            b"[1,\n 2",
+            b"\"unterminated",
+            b"tru",
+            b"nul ",
+            b"-",
+            b"1.",
+            b"]",
+            b"[}",
+            b"1,2",
+            b"[1 2]",
+            b"{\"a\" 1}",
+            b"[tx]",
+            b"x",
+            b"[01]",
+            b"[1.e2]",
+            b"[-]",
+            b"{\"a\":1,}",
+            b"{1:2}",
+            b"[1}",
+            b"[\"\\ud800x\"]",
+            b"[\"\\ud800\\u0041\"]",
+            b"[\"\\udc00\"]",
+            b"[\"\x01\"]",
+            b"[\"\xc3\xa9\" \xc3\xa9]",
+            b"12\xc3\xa9",
+            b"[1, \xe2\x82]",
+        ];
+        for src in corpus {
+            let expected = vec![from_slice(src).map_err(|err| (err.kind(), err.position()))];
+            for split in 0..=src.len() {
+                let (a, b) = src.split_at(split);
+                let outcomes = values(IncrementalParser::new(), &[a, b]);
+                let src_lossy = String::from_utf8_lossy(src);
+                assert_eq!(outcomes, expected, "{src_lossy:?} split at {split}");
+            }
+        }
+    }
+
+    #[test]
+    fn incremental_parser_matches_from_slice_with_limits() {
+        let corpus: &[&[u8]] = &[
+            b"[\"abc\", \"abcd\"]",
+            b"{\"abcd\": 1}",
+            b"[\"a\\n\\u00e9\", \"\\ud83d\\ude00\"]",
+            b"[\"\xc3\xa9\xc3\xa9\"]",
+            b"[123, 1234]",
+            b"[-1.5, 1e+10]",
+            b"[1, 2, 3]",
+            b"[[], [1, 2], [1,\n 2,\n 3]]",
+            b"{\"a\": [1, 2, \"abcd\"]}",
+        ];
+        for src in corpus {
+            let mut parser = SliceParser::new(src)
+                .max_string_len(3)
+                .max_number_len(4)
+                .max_array_len(2);
+            let expected = parser
+                .parse()
+                .and_then(|value| parser.end().map(|()| value));
+            let expected = vec![expected.map_err(|err| (err.kind(), err.position()))];
+            for split in 0..=src.len() {
+                let (a, b) = src.split_at(split);
+                let parser = IncrementalParser::new()
+                    .max_string_len(3)
+                    .max_number_len(4)
+                    .max_array_len(2);
+                let outcomes = values(parser, &[a, b]);
+                let src_lossy = String::from_utf8_lossy(src);
+                assert_eq!(outcomes, expected, "{src_lossy:?} split at {split}");
+            }
+        }
+    }
+
+    #[test]
+    fn incremental_parser_rejects_errors_early() {
+        let unterminated: [&[u8]; 7] = [
+            b"[1 2",
+            b"{\"a\" 1",
+            b"[1, nul ",
+            b"{\"a\": [tx",
+            b"x",
+            b"[\"\\x",
+            b"[1, ]",
+        ];
+        for src in unterminated {
+            let expected = from_slice(src).unwrap_err();
+            let mut parser = IncrementalParser::new();
+            parser.feed(src);
+            let err = parser.next_value().unwrap_err();
+            let src_lossy = String::from_utf8_lossy(src);
+            assert_eq!(err.to_string(), expected.to_string(), "{src_lossy:?}");
+        }
+
+        // bytes are checked when they are fed, not only where the next chunk starts
+        let mut parser = IncrementalParser::new();
+        parser.feed(b"[1 ");
+        assert_eq!(parser.next_value().unwrap(), Progress::NeedMoreInput);
+        parser.feed(b" 2 3");
+        let err = parser.next_value().unwrap_err();
+        assert_eq!(
+            (err.kind(), err.position().offset()),
+            (ErrorKind::UnexpectedChar, 4)
+        );
+    }
+
+    #[test]
+    fn incremental_parser_locates_errors() {
+        let chunks: [&[u8]; 3] = [b"[1]\n{\"a\"", b": [2,\n x]}", b" 3"];
+        let outcomes = values(IncrementalParser::new(), &chunks);
+        assert_eq!(outcomes.len(), 2);
+        let Err((kind, pos)) = outcomes[1] else {
+            panic!("expected an error");
+        };
+        assert_eq!(kind, ErrorKind::UnexpectedChar);
+        assert_eq!((pos.line(), pos.column(), pos.offset()), (3, 2, 15));
+
+        let mut parser = IncrementalParser::new();
+        parser.feed(b"[1, ");
+        assert!(parser.next_value().is_ok());
+        parser.feed(b"}");
+        assert!(parser.next_value().is_err());
+        assert_eq!(parser.next_value().unwrap(), Progress::Done);
+    }
+
+    #[test]
+    fn incremental_parser_checks_limits_early() {
+        let mut parser = IncrementalParser::new().max_depth(2);
+        parser.feed(b"[[");
+        assert_eq!(parser.next_value().unwrap(), Progress::NeedMoreInput);
+        parser.feed(b"[");
+        let err = parser.next_value().unwrap_err();
+        assert_eq!(
+            (err.kind(), err.position().offset()),
+            (ErrorKind::DepthLimit, 2)
+        );
+
+        let mut parser = IncrementalParser::new().max_string_len(3);
+        parser.feed(b"[\"ab\\n");
+        assert_eq!(parser.next_value().unwrap(), Progress::NeedMoreInput);
+        parser.feed(b"c");
+        let err = parser.next_value().unwrap_err();
+        assert_eq!(
+            (err.kind(), err.position().offset()),
+            (ErrorKind::StringTooLong, 1)
+        );
+
+        let mut parser = IncrementalParser::new().max_number_len(3);
+        parser.feed(b"[1, 2.34");
+        let err = parser.next_value().unwrap_err();
+        assert_eq!(
+            (err.kind(), err.position().offset()),
+            (ErrorKind::NumberTooLong, 4)
+        );
+
+        // the error is after the whitespace that follows the comma
+        let mut parser = IncrementalParser::new().max_array_len(2);
+        parser.feed(b"[1, 2,");
+        assert_eq!(parser.next_value().unwrap(), Progress::NeedMoreInput);
+        parser.feed(b"\n ");
+        assert_eq!(parser.next_value().unwrap(), Progress::NeedMoreInput);
+        parser.feed(b"3");
+        let err = parser.next_value().unwrap_err();
+        assert_eq!(
+            (err.kind(), err.position().offset()),
+            (ErrorKind::TooManyElements, 8)
+        );
+
+        let mut parser = IncrementalParser::new().max_input_len(10);
+        parser.feed(b"[1] [\"abc");
+        assert!(matches!(parser.next_value(), Ok(Progress::Value(_))));
+        assert_eq!(parser.next_value().unwrap(), Progress::NeedMoreInput);
+        parser.feed(b"def");
+        let err = parser.next_value().unwrap_err();
+        assert_eq!(
+            (err.kind(), err.position().offset()),
+            (ErrorKind::InputTooLong, 10)
+        );
+        assert_eq!(
+            err.to_string(),
+            "Parse json error at line 1 column 11: input is longer than the maximum of 10 bytes"
+        );
+    }
+}
@@ -0,0 +1,1640 @@
+pub mod array;
+pub mod borrowed;
+mod diagnostic;
+mod documents;
+mod error;
+mod events;
+pub mod format;
+mod incremental;
+pub mod map;
+pub mod ndjson;
+pub mod number;
+#[cfg(feature = "preserve_order")]
+mod ordered;
+mod slice;
+mod source;
+mod visitor;
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io;
+use std::mem;
+use std::str;
+
+pub use array::Array;
+pub use borrowed::BorrowedValue;
+pub use diagnostic::Diagnostic;
+pub use documents::{Documents, Framing};
+pub use error::{ErrorKind, JsonParserError, Position};
+pub use events::{Event, EventParser};
+use format::Formatter;
+pub use incremental::{IncrementalParser, Progress};
+pub use map::Map;
+pub use number::Number;
+pub use slice::SliceParser;
+pub use source::{IoRead, SliceRead, Source, SourceError};
+pub use visitor::{VisitError, Visitor};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(String),
+    Array(Array),
+    Object(Map),
+}
+
+impl Value {
+    pub fn as_number(&self) -> Option<&Number> {
+        match self {
+            Value::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_number().and_then(Number::as_i64)
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_number().and_then(Number::as_u64)
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        self.as_number().map(Number::as_f64)
+    }
+
+    /// Takes the string out of the value, or `None` for any other variant.
+    pub fn into_string(self) -> Option<String> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn into_array(self) -> Option<Vec<Value>> {
+        match self {
+            Value::Array(arr) => Some(arr.into_vec()),
+            _ => None,
+        }
+    }
+
+    pub fn into_object(self) -> Option<Map> {
+        match self {
+            Value::Object(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    fn is_container(&self) -> bool {
+        matches!(self, Value::Array(_) | Value::Object(_))
+    }
+}
+
+/// Drops `stack` and the values nested in it with a loop instead of recursion.
+fn drop_nested(mut stack: Vec<Value>) {
+    while let Some(mut value) = stack.pop() {
+        match &mut value {
+            Value::Array(arr) => stack.append(arr),
+            Value::Object(map) => stack.extend(mem::take(map).into_iter().map(|(_, v)| v)),
+            _ => {}
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Formatter::standard().format_to(f, self)
+    }
+}
+
+impl str::FromStr for Value {
+    type Err = JsonParserError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        from_str(src)
+    }
+}
+
+/// Parses `src` as a single JSON document. Whitespace around the root value is allowed,
+/// but anything else after it is reported as an error.
+pub fn from_str(src: &str) -> Result<Value, JsonParserError> {
+    from_slice(src.as_bytes())
+}
+
+/// Parses a single json value from UTF-8 bytes, like [`from_str`]. Invalid UTF-8 fails
+/// with [`ErrorKind::InvalidUtf8`] at the position of the offending byte.
+pub fn from_slice(src: &[u8]) -> Result<Value, JsonParserError> {
+    let mut parser = SliceParser::new(src);
+    let value = parser.parse()?;
+    parser.end()?;
+    Ok(value)
+}
+
+/// Parses a single json value from a reader, like [`from_str`]. The whole input is read,
+/// to check that nothing follows the value.
+pub fn from_reader<R: io::Read>(reader: R) -> Result<Value, JsonParserError> {
+    let mut parser = JsonParser::from_reader(reader);
+    let value = parser.parse()?;
+    parser.end()?;
+    Ok(value)
+}
+
+pub struct JsonParser<T: Source> {
+    src: T,
+    /// The character after `pos`, once it has been read from `src`.
+    peeked: Option<Option<char>>,
+    pos: Position,
+    options: Options,
+}
+
+/// How [`JsonParser`] handles a key that appears more than once in the same object.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// Fail with [`ErrorKind::DuplicateKey`], pointing at both occurrences.
+    Error,
+    /// Keep the value of the first occurrence.
+    FirstWins,
+    /// Keep the value of the last occurrence.
+    #[default]
+    LastWins,
+    /// Collect the values of every occurrence into an array, in document order.
+    Collect,
+}
+
+/// Default for [`JsonParser::max_depth`].
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// Settings shared by [`JsonParser`], [`SliceParser`] and [`IncrementalParser`], see their
+/// setters.
+#[derive(Clone, Debug)]
+struct Options {
+    arbitrary_precision: bool,
+    max_depth: usize,
+    max_input_len: usize,
+    max_string_len: usize,
+    max_number_len: usize,
+    max_array_len: usize,
+    max_object_len: usize,
+    duplicate_keys: DuplicateKeys,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            arbitrary_precision: false,
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_input_len: usize::MAX,
+            max_string_len: usize::MAX,
+            max_number_len: usize::MAX,
+            max_array_len: usize::MAX,
+            max_object_len: usize::MAX,
+            duplicate_keys: DuplicateKeys::default(),
+        }
+    }
+}
+
+impl<'a> JsonParser<SliceRead<'a>> {
+    /// A parser over UTF-8 bytes, which are validated as they are parsed.
+    pub fn from_slice(src: &'a [u8]) -> Self {
+        Self::new(SliceRead::new(src))
+    }
+}
+
+impl<R: io::Read> JsonParser<IoRead<R>> {
+    /// A parser over the UTF-8 bytes of `reader`, which are read in buffered chunks and
+    /// validated as they are parsed.
+    pub fn from_reader(reader: R) -> Self {
+        Self::new(IoRead::new(reader))
+    }
+}
+
+impl<T: Source> JsonParser<T> {
+    pub fn new(src: T) -> Self {
+        Self {
+            src,
+            peeked: None,
+            pos: Position::start(),
+            options: Options::default(),
+        }
+    }
+
+    /// Maximum number of arrays and objects that may be nested inside each other,
+    /// defaults to [`DEFAULT_MAX_DEPTH`]. Deeper input fails with [`ErrorKind::DepthLimit`].
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.options.max_depth = max_depth;
+        self
+    }
+
+    /// Maximum number of bytes read from the input, whitespace included. Longer input
+    /// fails with [`ErrorKind::InputTooLong`]. Unlimited by default.
+    pub fn max_input_len(mut self, max_input_len: usize) -> Self {
+        self.options.max_input_len = max_input_len;
+        self
+    }
+
+    /// Maximum length in bytes of a string or object key once escapes are decoded.
+    /// Longer strings fail with [`ErrorKind::StringTooLong`]. Unlimited by default.
+    pub fn max_string_len(mut self, max_string_len: usize) -> Self {
+        self.options.max_string_len = max_string_len;
+        self
+    }
+
+    /// Maximum number of characters in a number literal. Longer numbers fail with
+    /// [`ErrorKind::NumberTooLong`]. Unlimited by default.
+    pub fn max_number_len(mut self, max_number_len: usize) -> Self {
+        self.options.max_number_len = max_number_len;
+        self
+    }
+
+    /// Maximum number of elements in a single array. Larger arrays fail with
+    /// [`ErrorKind::TooManyElements`]. Unlimited by default.
+    pub fn max_array_len(mut self, max_array_len: usize) -> Self {
+        self.options.max_array_len = max_array_len;
+        self
+    }
+
+    /// Maximum number of members in a single object. Larger objects fail with
+    /// [`ErrorKind::TooManyMembers`]. Unlimited by default.
+    pub fn max_object_len(mut self, max_object_len: usize) -> Self {
+        self.options.max_object_len = max_object_len;
+        self
+    }
+
+    /// What to do with keys that appear more than once in the same object, defaults to
+    /// [`DuplicateKeys::LastWins`].
+    pub fn duplicate_keys(mut self, duplicate_keys: DuplicateKeys) -> Self {
+        self.options.duplicate_keys = duplicate_keys;
+        self
+    }
+
+    /// When enabled, numbers keep their exact decimal text (see [`Number::from_raw`])
+    /// instead of being converted to an integer or `f64`.
+    pub fn arbitrary_precision(mut self, arbitrary_precision: bool) -> Self {
+        self.options.arbitrary_precision = arbitrary_precision;
+        self
+    }
+
+    /// Parses the next value, skipping any whitespace before it. Input after the value
+    /// is left untouched, so it can be called repeatedly to read a stream of documents.
+    ///
+    /// Nested arrays and objects are tracked on an explicit stack rather than through
+    /// recursion, so deeply nested input cannot overflow the thread stack.
+    pub fn parse(&mut self) -> Result<Value, JsonParserError> {
+        let mut stack = Vec::<Frame>::new();
+        loop {
+            self.skip_whitespace()?;
+            let mut value = match self.peek()? {
+                Some('[') => {
+                    self.enter(stack.len())?;
+                    self.skip_whitespace()?;
+                    if let Some(']') = self.peek()? {
+                        self.eat()?;
+                        Value::Array(Array::new())
+                    } else {
+                        self.check_array_len(0)?;
+                        stack.push(Frame::Array(Vec::new()));
+                        continue;
+                    }
+                }
+                Some('{') => {
+                    self.enter(stack.len())?;
+                    self.skip_whitespace()?;
+                    if let Some('}') = self.peek()? {
+                        self.eat()?;
+                        Value::Object(Map::new())
+                    } else {
+                        self.check_object_len(0)?;
+                        let mut object = ObjectFrame::default();
+                        self.parse_member_key(&mut object)?;
+                        stack.push(Frame::Object(object));
+                        continue;
+                    }
+                }
+                _ => self.parse_scalar()?,
+            };
+
+            // attach the finished value to its parent, closing every container that
+            // ends right after it
+            loop {
+                match stack.last_mut() {
+                    None => return Ok(value),
+                    Some(Frame::Array(values)) => {
+                        values.push(value);
+                        if self.parse_separator(']')? {
+                            self.check_array_len(values.len())?;
+                            break;
+                        }
+                        let Some(Frame::Array(values)) = stack.pop() else {
+                            unreachable!("last frame should be an array");
+                        };
+                        value = Value::Array(values.into());
+                    }
+                    Some(Frame::Object(object)) => {
+                        object.insert(value, self.options.duplicate_keys);
+                        if self.parse_separator('}')? {
+                            self.check_object_len(object.members.len())?;
+                            self.parse_member_key(object)?;
+                            break;
+                        }
+                        let Some(Frame::Object(object)) = stack.pop() else {
+                            unreachable!("last frame should be an object");
+                        };
+                        value = Value::Object(object.members);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Turns the parser into a pull parser that reports the next value as a sequence of
+    /// [`Event`]s, see [`EventParser`].
+    pub fn events(self) -> EventParser<T> {
+        EventParser::new(self)
+    }
+
+    /// Turns the parser into an iterator over a stream of documents delimited as
+    /// `framing` says, see [`Documents`].
+    pub fn documents(self, framing: Framing) -> Documents<T> {
+        Documents::new(self, framing)
+    }
+
+    /// Parses the next value like [`parse`](Self::parse), calling `visitor` for each of
+    /// its parts instead of building a [`Value`].
+    pub fn visit<V: Visitor>(&mut self, visitor: &mut V) -> Result<(), VisitError<V::Error>> {
+        let mut reader = events::Reader::new();
+        while let Some((event, position)) = reader.read(self)? {
+            let visited = match event {
+                Event::StartObject => visitor.start_object(),
+                Event::Key(key) => visitor.key(key),
+                Event::EndObject => visitor.end_object(),
+                Event::StartArray => visitor.start_array(),
+                Event::EndArray => visitor.end_array(),
+                Event::String(s) => visitor.string(s),
+                Event::Number(n) => visitor.number(n),
+                Event::Bool(b) => visitor.bool(b),
+                Event::Null => visitor.null(),
+            };
+            visited.map_err(|error| VisitError::Visitor { error, position })?;
+        }
+        Ok(())
+    }
+
+    fn parse_scalar(&mut self) -> Result<Value, JsonParserError> {
+        match self.peek()? {
+            Some('t') => self.parse_true(),
+            Some('f') => self.parse_false(),
+            Some('n') => self.parse_null(),
+            Some('"') => self.parse_string(),
+            Some(ch) if ch == '-' || ch.is_ascii_digit() => self.parse_number(),
+            ch => Err(self.unexpected_value(ch)),
+        }
+    }
+
+    /// Error for `ch`, or the end of input, found where a value should start.
+    fn unexpected_value(&self, ch: Option<char>) -> JsonParserError {
+        match ch {
+            Some(ch) => {
+                let msg = format!("unexpected character '{ch}'");
+                self.unexpected(ch, "value", msg)
+            }
+            None => self.eof().with_expected("value"),
+        }
+    }
+
+    /// Checks that only whitespace remains in the input.
+    pub fn end(&mut self) -> Result<(), JsonParserError> {
+        self.skip_whitespace()?;
+        match self.peek()? {
+            Some(ch) => {
+                let msg = format!("trailing characters after json value, received '{ch}'");
+                Err(self
+                    .error(ErrorKind::TrailingCharacters, msg)
+                    .with_found(ch))
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn eof(&self) -> JsonParserError {
+        self.error(ErrorKind::UnexpectedEof, "unexpected end of input")
+    }
+
+    fn error(&self, kind: ErrorKind, msg: impl Into<String>) -> JsonParserError {
+        JsonParserError::new(kind, msg, self.pos)
+    }
+
+    /// Error for an unexpected `ch` that has been peeked but not consumed yet.
+    fn unexpected(&self, ch: char, expected: &'static str, msg: String) -> JsonParserError {
+        self.error(ErrorKind::UnexpectedChar, msg)
+            .with_expected(expected)
+            .with_found(ch)
+    }
+
+    fn is_whitespace(&self, ch: char) -> bool {
+        matches!(ch, ' ' | '\t' | '\n' | '\r')
+    }
+
+    fn next_pos(&mut self, ch: char) {
+        if ch == '\n' {
+            self.pos.col = 1;
+            self.pos.line += 1;
+        } else {
+            self.pos.col += 1;
+        }
+        self.pos.offset += ch.len_utf8();
+    }
+
+    fn skip_whitespace(&mut self) -> Result<(), JsonParserError> {
+        while let Some(ch) = self.peek()? {
+            if self.is_whitespace(ch) {
+                self.eat()?;
+            } else {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the next character without consuming it.
+    fn peek(&mut self) -> Result<Option<char>, JsonParserError> {
+        if let Some(peeked) = self.peeked {
+            return Ok(peeked);
+        }
+        let peeked = match self.src.next_char() {
+            Ok(peeked) => peeked,
+            Err(err) => return Err(self.source_error(err)),
+        };
+        self.peeked = Some(peeked);
+        Ok(peeked)
+    }
+
+    /// Kept out of [`peek`](Self::peek), so the path of every valid character stays small.
+    #[cold]
+    fn source_error(&self, err: SourceError) -> JsonParserError {
+        match err {
+            SourceError::InvalidUtf8 => {
+                self.error(ErrorKind::InvalidUtf8, "invalid UTF-8 in input")
+            }
+            SourceError::Io(err) => {
+                let msg = format!("failed reading input: {err}");
+                self.error(ErrorKind::Io, msg).with_io(err)
+            }
+        }
+    }
+
+    fn eat(&mut self) -> Result<char, JsonParserError> {
+        let Some(ch) = self.peek()? else {
+            return Err(self.eof());
+        };
+        self.peeked = None;
+        if self.pos.offset + ch.len_utf8() > self.options.max_input_len {
+            let msg = format!(
+                "input is longer than the maximum of {} bytes",
+                self.options.max_input_len
+            );
+            return Err(self.error(ErrorKind::InputTooLong, msg));
+        }
+        self.next_pos(ch);
+        Ok(ch)
+    }
+
+    fn read_word(&mut self, word: &'static str) -> Result<(), JsonParserError> {
+        for w in word.chars() {
+            let Some(ch) = self.peek()? else {
+                return Err(self.eof().with_expected(word));
+            };
+            if ch != w {
+                let msg =
+                    format!("failed parsing {word} - expected character '{w}' but received '{ch}'");
+                return Err(self.unexpected(ch, word, msg));
+            }
+            self.eat()?;
+        }
+        Ok(())
+    }
+
+    fn parse_null(&mut self) -> Result<Value, JsonParserError> {
+        self.read_word("null").map(|_| Value::Null)
+    }
+
+    fn parse_true(&mut self) -> Result<Value, JsonParserError> {
+        self.read_word("true").map(|_| Value::Bool(true))
+    }
+
+    fn parse_false(&mut self) -> Result<Value, JsonParserError> {
+        self.read_word("false").map(|_| Value::Bool(false))
+    }
+
+    fn parse_number(&mut self) -> Result<Value, JsonParserError> {
+        self.read_number().map(Value::Number)
+    }
+
+    fn read_number(&mut self) -> Result<Number, JsonParserError> {
+        let start = self.pos;
+        let mut buf = String::new();
+        if let Some('-') = self.peek()? {
+            self.read_number_char(&mut buf, start)?;
+        }
+
+        let zero = self.pos;
+        let first = self.read_digit(&mut buf, start)?;
+        if first == '0' {
+            if let Some('0'..='9') = self.peek()? {
+                let msg = "leading zeros are not allowed in numbers";
+                return Err(JsonParserError::new(ErrorKind::InvalidNumber, msg, zero));
+            }
+        } else {
+            self.read_digits(&mut buf, start)?;
+        }
+
+        if let Some('.') = self.peek()? {
+            self.read_number_char(&mut buf, start)?;
+            self.read_digit(&mut buf, start)?;
+            self.read_digits(&mut buf, start)?;
+        }
+
+        if let Some('e' | 'E') = self.peek()? {
+            self.read_number_char(&mut buf, start)?;
+            if let Some('+' | '-') = self.peek()? {
+                self.read_number_char(&mut buf, start)?;
+            }
+            self.read_digit(&mut buf, start)?;
+            self.read_digits(&mut buf, start)?;
+        }
+
+        match number_from_text(&buf, self.options.arbitrary_precision) {
+            Some(number) => Ok(number),
+            None => {
+                let msg = format!("number '{buf}' is out of range");
+                Err(JsonParserError::new(
+                    ErrorKind::NumberOutOfRange,
+                    msg,
+                    start,
+                ))
+            }
+        }
+    }
+
+    /// Reads exactly one ASCII digit into `buf`, reporting the offending character
+    /// at its own position otherwise.
+    fn read_digit(&mut self, buf: &mut String, start: Position) -> Result<char, JsonParserError> {
+        match self.peek()? {
+            Some(ch) if ch.is_ascii_digit() => self.read_number_char(buf, start),
+            Some(ch) => {
+                let msg = format!("expected a digit but received character '{ch}'");
+                Err(self
+                    .error(ErrorKind::InvalidNumber, msg)
+                    .with_expected("digit")
+                    .with_found(ch))
+            }
+            None => Err(self.eof().with_expected("digit")),
+        }
+    }
+
+    fn read_digits(&mut self, buf: &mut String, start: Position) -> Result<(), JsonParserError> {
+        while let Some('0'..='9') = self.peek()? {
+            self.read_number_char(buf, start)?;
+        }
+        Ok(())
+    }
+
+    /// Moves the next character of the number starting at `start` into `buf`.
+    fn read_number_char(
+        &mut self,
+        buf: &mut String,
+        start: Position,
+    ) -> Result<char, JsonParserError> {
+        if buf.len() >= self.options.max_number_len {
+            let msg = format!(
+                "number is longer than the maximum of {} characters",
+                self.options.max_number_len
+            );
+            return Err(JsonParserError::new(ErrorKind::NumberTooLong, msg, start));
+        }
+        let ch = self.eat()?;
+        buf.push(ch);
+        Ok(ch)
+    }
+
+    fn parse_string(&mut self) -> Result<Value, JsonParserError> {
+        self.parse_str().map(Value::String)
+    }
+
+    fn parse_str(&mut self) -> Result<String, JsonParserError> {
+        let mut buf = String::new();
+        self.read_str(Some(&mut buf))?;
+        Ok(buf)
+    }
+
+    /// Reads a string into `buf`, or only checks it when `buf` is `None`.
+    fn read_str(&mut self, mut buf: Option<&mut String>) -> Result<(), JsonParserError> {
+        let start = self.pos;
+        assert_eq!(self.eat()?, '"', "string should start with quotes");
+
+        let mut len = 0;
+        loop {
+            let pos = self.pos;
+            let ch = match self.eat()? {
+                '"' => break,
+                '\\' => self.parse_escape(pos)?,
+                ch if ch < '\u{20}' => {
+                    let msg = format!(
+                        "unescaped control character '\\u{:04X}' in string",
+                        u32::from(ch)
+                    );
+                    let err = JsonParserError::new(ErrorKind::ControlCharacter, msg, pos);
+                    return Err(err.with_found(ch));
+                }
+                ch => ch,
+            };
+            if let Some(buf) = buf.as_deref_mut() {
+                buf.push(ch);
+            }
+
+            len += ch.len_utf8();
+            if len > self.options.max_string_len {
+                let msg = format!(
+                    "string is longer than the maximum of {} bytes",
+                    self.options.max_string_len
+                );
+                return Err(JsonParserError::new(ErrorKind::StringTooLong, msg, start));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Decodes the escape sequence following a `\`. `start` points at the backslash,
+    /// so errors about the sequence as a whole are reported there.
+    fn parse_escape(&mut self, start: Position) -> Result<char, JsonParserError> {
+        let ch = match self.eat()? {
+            '"' => '"',
+            '\\' => '\\',
+            '/' => '/',
+            'b' => '\u{08}',
+            'f' => '\u{0C}',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'u' => return self.parse_unicode_escape(start),
+            ch => {
+                let msg = format!("invalid escape sequence '\\{ch}'");
+                let err = JsonParserError::new(ErrorKind::InvalidEscape, msg, start);
+                return Err(err.with_found(ch));
+            }
+        };
+        Ok(ch)
+    }
+
+    fn parse_unicode_escape(&mut self, start: Position) -> Result<char, JsonParserError> {
+        let code = self.parse_hex_code()?;
+        match code {
+            0xD800..=0xDBFF => {
+                if self.peek()? != Some('\\') {
+                    let msg = format!("lone leading surrogate '\\u{code:04X}' in string");
+                    let err = JsonParserError::new(ErrorKind::InvalidUnicode, msg, start);
+                    return Err(err.with_expected("trailing surrogate"));
+                }
+
+                let low_start = self.pos;
+                self.eat()?;
+                let ch = self.eat()?;
+                if ch != 'u' {
+                    let msg = format!(
+                        "expected trailing surrogate after '\\u{code:04X}' but received '\\{ch}'"
+                    );
+                    let err = JsonParserError::new(ErrorKind::InvalidUnicode, msg, low_start);
+                    return Err(err.with_expected("trailing surrogate").with_found(ch));
+                }
+
+                let low = self.parse_hex_code()?;
+                if !(0xDC00..=0xDFFF).contains(&low) {
+                    let msg = format!(
+                        "expected trailing surrogate after '\\u{code:04X}' but received '\\u{low:04X}'"
+                    );
+                    let err = JsonParserError::new(ErrorKind::InvalidUnicode, msg, low_start);
+                    return Err(err.with_expected("trailing surrogate"));
+                }
+
+                let scalar = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                Ok(char::from_u32(scalar).expect("surrogate pair should decode to a valid char"))
+            }
+            0xDC00..=0xDFFF => {
+                let msg = format!("lone trailing surrogate '\\u{code:04X}' in string");
+                Err(JsonParserError::new(ErrorKind::InvalidUnicode, msg, start))
+            }
+            _ => Ok(char::from_u32(code).expect("non surrogate code point should be a valid char")),
+        }
+    }
+
+    fn parse_hex_code(&mut self) -> Result<u32, JsonParserError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let Some(ch) = self.peek()? else {
+                return Err(self.eof().with_expected("hexadecimal digit"));
+            };
+            let Some(digit) = ch.to_digit(16) else {
+                let msg = format!("expected a hexadecimal digit but received '{ch}'");
+                return Err(self
+                    .error(ErrorKind::InvalidEscape, msg)
+                    .with_expected("hexadecimal digit")
+                    .with_found(ch));
+            };
+            self.eat()?;
+            code = code * 16 + digit;
+        }
+        Ok(code)
+    }
+
+    /// Consumes the opening bracket of a container nested inside `depth` others.
+    fn enter(&mut self, depth: usize) -> Result<(), JsonParserError> {
+        if depth >= self.options.max_depth {
+            let msg = format!(
+                "maximum nesting depth of {} exceeded",
+                self.options.max_depth
+            );
+            return Err(self.error(ErrorKind::DepthLimit, msg));
+        }
+        self.eat()?;
+        Ok(())
+    }
+
+    /// Checks that an array holding `len` elements may receive another one, reporting
+    /// the error at the start of the rejected element.
+    fn check_array_len(&mut self, len: usize) -> Result<(), JsonParserError> {
+        if len < self.options.max_array_len {
+            return Ok(());
+        }
+        self.skip_whitespace()?;
+        let msg = format!(
+            "array has more than the maximum of {} elements",
+            self.options.max_array_len
+        );
+        Err(self.error(ErrorKind::TooManyElements, msg))
+    }
+
+    /// Checks that an object holding `len` members may receive another one, reporting
+    /// the error at the start of the rejected member.
+    fn check_object_len(&mut self, len: usize) -> Result<(), JsonParserError> {
+        if len < self.options.max_object_len {
+            return Ok(());
+        }
+        self.skip_whitespace()?;
+        let msg = format!(
+            "object has more than the maximum of {} members",
+            self.options.max_object_len
+        );
+        Err(self.error(ErrorKind::TooManyMembers, msg))
+    }
+
+    /// Parses the key of the next member of `object`, applying the duplicate key policy
+    /// as far as it can be decided before the value is parsed.
+    fn parse_member_key(&mut self, object: &mut ObjectFrame) -> Result<(), JsonParserError> {
+        let (key, pos) = self.parse_key()?;
+        if self.options.duplicate_keys == DuplicateKeys::Error {
+            if let Some(&first) = object.positions.get(&key) {
+                return Err(duplicate_key(&key, first, pos));
+            }
+            object.positions.insert(key.clone(), pos);
+        }
+        object.key = key;
+        Ok(())
+    }
+
+    /// Parses an object key and the ':' after it, returning the key and its position.
+    fn parse_key(&mut self) -> Result<(String, Position), JsonParserError> {
+        let mut key = String::new();
+        let pos = self.read_key(Some(&mut key))?;
+        Ok((key, pos))
+    }
+
+    /// Reads an object key into `buf`, or only checks it when `buf` is `None`, and the
+    /// ':' after it. Returns the position of the key.
+    fn read_key(&mut self, buf: Option<&mut String>) -> Result<Position, JsonParserError> {
+        self.skip_whitespace()?;
+        let pos = self.pos;
+        match self.peek()? {
+            Some('"') => self.read_str(buf)?,
+            Some(ch) => {
+                let msg = "expected object key to be a string";
+                return Err(self.unexpected(ch, "string", String::from(msg)));
+            }
+            None => return Err(self.eof().with_expected("string")),
+        };
+
+        self.skip_whitespace()?;
+        match self.peek()? {
+            Some(':') => {
+                self.eat()?;
+                Ok(pos)
+            }
+            Some(ch) => {
+                let msg = format!("expected character ':' after an object key but received '{ch}'");
+                Err(self.unexpected(ch, "':'", msg))
+            }
+            None => Err(self.eof().with_expected("':'")),
+        }
+    }
+
+    /// Consumes the character after a container element, returning `true` if it was a
+    /// ',' and `false` if it was the closing character `end`.
+    fn parse_separator(&mut self, end: char) -> Result<bool, JsonParserError> {
+        let expected = if end == ']' {
+            "',' or ']'"
+        } else {
+            "',' or '}'"
+        };
+        self.skip_whitespace()?;
+        match self.peek()? {
+            Some(',') => {
+                self.eat()?;
+                Ok(true)
+            }
+            Some(ch) if ch == end => {
+                self.eat()?;
+                Ok(false)
+            }
+            Some(ch) => {
+                let msg = if end == ']' {
+                    format!(
+                        "expected either array value separator ',' or end of array character ']', but received '{ch}'"
+                    )
+                } else {
+                    format!(
+                        "expected either object key value separator ',' or end of character '}}', but received '{ch}'"
+                    )
+                };
+                Err(self.unexpected(ch, expected, msg))
+            }
+            None => Err(self.eof().with_expected(expected)),
+        }
+    }
+}
+
+/// Error for `key` found again at `pos` in an object where it first appeared at `first`.
+fn duplicate_key(key: &str, first: Position, pos: Position) -> JsonParserError {
+    let msg = format!(
+        "duplicate object key '{key}', first defined at line {} column {}",
+        first.line, first.col
+    );
+    JsonParserError::new(ErrorKind::DuplicateKey, msg, pos).with_first_occurrence(first)
+}
+
+/// Converts the text of a number that follows the json grammar, returning `None` if it
+/// is too large for an `f64`.
+fn number_from_text(text: &str, arbitrary_precision: bool) -> Option<Number> {
+    if arbitrary_precision {
+        let number = Number::from_raw(text).expect("number should follow the json grammar");
+        return Some(number);
+    }
+
+    let is_integer = !text.contains(['.', 'e', 'E']);
+    if is_integer {
+        if let Ok(n) = text.parse::<u64>() {
+            return Some(Number::from(n));
+        }
+        if let Ok(n) = text.parse::<i64>()
+            && n < 0
+        {
+            return Some(Number::from(n));
+        }
+    }
+
+    // `-0` and integers that overflow 64 bits fall back to a float
+    let number = text
+        .parse::<f64>()
+        .expect("number should follow the json grammar");
+    number.is_finite().then(|| Number::from_f64(number))
+}
+
+/// A container that is still being parsed, an array of `V` or an object `O`.
+enum Frame<V = Value, O = ObjectFrame> {
+    Array(Vec<V>),
+    Object(O),
+}
+
+/// An object that is still being parsed. `P` locates keys for duplicate key errors.
+struct ObjectFrame<P = Position> {
+    members: Map,
+    /// Key of the member being parsed.
+    key: String,
+    /// Position of every key, only tracked for [`DuplicateKeys::Error`].
+    positions: HashMap<String, P>,
+    /// Keys whose values were already collected into an array, only tracked for
+    /// [`DuplicateKeys::Collect`].
+    collected: HashSet<String>,
+}
+
+impl<P> ObjectFrame<P> {
+    /// Adds the member whose key was parsed last.
+    fn insert(&mut self, value: Value, duplicate_keys: DuplicateKeys) {
+        let key = mem::take(&mut self.key);
+        match duplicate_keys {
+            DuplicateKeys::Error | DuplicateKeys::LastWins => {
+                self.members.insert(key, value);
+            }
+            DuplicateKeys::FirstWins => {
+                if !self.members.contains_key(&key) {
+                    self.members.insert(key, value);
+                }
+            }
+            DuplicateKeys::Collect => match self.members.get_mut(&key) {
+                None => {
+                    self.members.insert(key, value);
+                }
+                Some(Value::Array(values)) if self.collected.contains(&key) => {
+                    values.push(value);
+                }
+                Some(existing) => {
+                    let first = mem::replace(existing, Value::Null);
+                    *existing = Value::Array(vec![first, value].into());
+                    self.collected.insert(key);
+                }
+            },
+        }
+    }
+}
+
+impl<P> Default for ObjectFrame<P> {
+    fn default() -> Self {
+        Self {
+            members: Map::new(),
+            key: String::new(),
+            positions: HashMap::new(),
+            collected: HashSet::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_null_works() {
+        let mut parser = JsonParser::new("null".chars());
+        let parsed = parser.parse_null();
+        assert!(parsed.is_ok(), "should be able to parse null");
+
+        let value = parsed.unwrap();
+        assert_eq!(value, Value::Null);
+    }
+
+    #[test]
+    fn parse_true_works() {
+        let mut parser = JsonParser::new("true".chars());
+        let parsed = parser.parse_true();
+        assert!(parsed.is_ok(), "should be able to parse true");
+
+        let value = parsed.unwrap();
+        assert_eq!(value, Value::Bool(true));
+    }
+
+    #[test]
+    fn parse_false_works() {
+        let mut parser = JsonParser::new("false".chars());
+        let parsed = parser.parse_false();
+        assert!(parsed.is_ok(), "should be able to parse false");
+
+        let value = parsed.unwrap();
+        assert_eq!(value, Value::Bool(false));
+    }
+
+    #[test]
+    fn parse_int_works() {
+        let ints = [1, 2, 3, 4, 10, 123, 1234];
+        for int in ints {
+            let src = int.to_string();
+            let mut parser = JsonParser::new(src.chars());
+            let parsed = parser.parse_number();
+            assert!(parsed.is_ok(), "should be able to parse int");
+
+            let value = parsed.unwrap();
+            assert_eq!(value, Value::Number(Number::from(int)));
+        }
+    }
+
+    #[test]
+    fn parse_float_works() {
+        let floats = [1.0, 1.1, 1.2, 2.12, 1.123, 1.1234, 1234.1234];
+        for float in floats {
+            let src = float.to_string();
+            let mut parser = JsonParser::new(src.chars());
+            let parsed = parser.parse_number();
+            assert!(parsed.is_ok(), "should be able to parse float");
+
+            let value = parsed.unwrap();
+            assert_eq!(value.as_f64(), Some(float));
+        }
+    }
+
+    #[test]
+    fn parse_number_grammar_works() {
+        let numbers = [
+            ("0", 0.0),
+            ("-0", -0.0),
+            ("-5", -5.0),
+            ("-12.5", -12.5),
+            ("0.25", 0.25),
+            ("1e10", 1e10),
+            ("1E10", 1e10),
+            ("1e+2", 100.0),
+            ("2.5E-3", 2.5e-3),
+            ("-0.5e1", -5.0),
+            ("0e0", 0.0),
+            ("1e007", 1e7),
+            ("1.7976931348623157e308", f64::MAX),
+        ];
+        for (src, out) in numbers {
+            let mut parser = JsonParser::new(src.chars());
+            let parsed = parser.parse();
+            assert!(parsed.is_ok(), "should be able to parse {src}");
+            assert_eq!(parsed.unwrap().as_f64(), Some(out), "wrong value for {src}");
+        }
+    }
+
+    #[test]
+    fn parse_integers_keeps_precision() {
+        let mut parser = JsonParser::new("9007199254740993".chars());
+        assert_eq!(parser.parse().unwrap().as_u64(), Some(9007199254740993));
+
+        let mut parser = JsonParser::new("18446744073709551615".chars());
+        assert_eq!(parser.parse().unwrap().as_u64(), Some(u64::MAX));
+
+        let mut parser = JsonParser::new("-9223372036854775808".chars());
+        assert_eq!(parser.parse().unwrap().as_i64(), Some(i64::MIN));
+
+        let mut parser = JsonParser::new("18446744073709551616".chars());
+        let value = parser.parse().unwrap();
+        assert_eq!(value.as_u64(), None);
+        assert_eq!(value.as_f64(), Some(18446744073709551616.0));
+
+        let mut parser = JsonParser::new("-0".chars());
+        let value = parser.parse().unwrap();
+        assert!(value.as_number().unwrap().is_f64());
+    }
+
+    #[test]
+    fn parse_arbitrary_precision_works() {
+        let src = r#"[0.1000000000000000000001, 1e400, 123456789012345678901234567890, -0]"#;
+        let mut parser = JsonParser::new(src.chars()).arbitrary_precision(true);
+        let Value::Array(arr) = parser.parse().unwrap() else {
+            panic!("should have parsed an array");
+        };
+        let raws: Vec<_> = arr
+            .iter()
+            .map(|v| v.as_number().unwrap().as_raw().unwrap())
+            .collect();
+        assert_eq!(
+            raws,
+            [
+                "0.1000000000000000000001",
+                "1e400",
+                "123456789012345678901234567890",
+                "-0"
+            ]
+        );
+
+        let mut parser = JsonParser::new("01".chars()).arbitrary_precision(true);
+        assert!(parser.parse().is_err(), "grammar should still be enforced");
+    }
+
+    #[test]
+    fn parse_number_errors_works() {
+        let errors = [
+            ("012", (1, 1)),
+            ("-012", (1, 2)),
+            ("00", (1, 1)),
+            ("-", (1, 2)),
+            ("-a", (1, 2)),
+            ("--1", (1, 2)),
+            ("1.", (1, 3)),
+            ("1.e5", (1, 3)),
+            ("1e", (1, 3)),
+            ("1e+", (1, 4)),
+            ("1ex", (1, 3)),
+            ("1.5e-x", (1, 6)),
+            ("1e400", (1, 1)),
+            ("-1e400", (1, 1)),
+        ];
+        for (src, pos) in errors {
+            let mut parser = JsonParser::new(src.chars());
+            let parsed = parser.parse();
+            assert!(parsed.is_err(), "should fail to parse {src}");
+
+            let err = parsed.unwrap_err();
+            assert_eq!((err.line(), err.column()), pos, "wrong position for {src}");
+        }
+    }
+
+    #[test]
+    fn parse_negative_number_in_containers_works() {
+        let mut parser = JsonParser::new(r#"[-1, {"a": -2.5e1}]"#.chars());
+        let Value::Array(arr) = parser.parse().unwrap() else {
+            panic!("should have parsed an array");
+        };
+        assert_eq!(arr[0], Value::Number(Number::from(-1)));
+        let Value::Object(map) = &arr[1] else {
+            panic!("should have parsed an object");
+        };
+        assert_eq!(map.get("a"), Some(&Value::Number(Number::from(-25.0))));
+    }
+
+    #[test]
+    fn parse_string_works() {
+        let strs = [
+            (r#""test""#, String::from("test")),
+            (r#""hironha""#, String::from("hironha")),
+            (r#""a""#, String::from("a")),
+        ];
+        for (src, out) in strs {
+            let mut parser = JsonParser::new(src.chars());
+            let parsed = parser.parse_string();
+            assert!(parsed.is_ok(), "should be able to parse strign");
+
+            let value = parsed.unwrap();
+            assert_eq!(value, Value::String(out));
+        }
+    }
+
+    #[test]
+    fn parse_string_escapes_works() {
+        let strs = [
+            (r#""a\"b""#, "a\"b"),
+            (r#""a\\b""#, "a\\b"),
+            (r#""a\/b""#, "a/b"),
+            (r#""\b\f\n\r\t""#, "\u{08}\u{0C}\n\r\t"),
+            (r#""\u0041\u00e9\u20AC""#, "Aé€"),
+            (r#""\u0000""#, "\u{0}"),
+            (r#""\uD83D\uDE00""#, "😀"),
+            (r#""héllo wörld""#, "héllo wörld"),
+        ];
+        for (src, out) in strs {
+            let mut parser = JsonParser::new(src.chars());
+            let parsed = parser.parse_string();
+            assert!(parsed.is_ok(), "should be able to parse {src}");
+
+            let value = parsed.unwrap();
+            assert_eq!(value, Value::String(String::from(out)));
+        }
+    }
+
+    #[test]
+    fn parse_string_tracks_position() {
+        let src = "[\"a\\nb\", x]";
+        let mut parser = JsonParser::new(src.chars());
+        let err = parser.parse().unwrap_err();
+        assert_eq!((err.line(), err.column()), (1, 10));
+    }
+
+    #[test]
+    fn parse_string_errors_works() {
+        let errors = [
+            ("\"a\nb\"", (1, 3)),
+            ("\"a\u{1F}\"", (1, 3)),
+            (r#""ab\x""#, (1, 4)),
+            (r#""\u12G4""#, (1, 6)),
+            (r#""\uD83D""#, (1, 2)),
+            (r#""\uD83Dx""#, (1, 2)),
+            (r#""\uD83D\n""#, (1, 8)),
+            (r#""\uD83D\u0041""#, (1, 8)),
+            (r#""\uDE00""#, (1, 2)),
+            (r#""abc"#, (1, 5)),
+        ];
+        for (src, pos) in errors {
+            let mut parser = JsonParser::new(src.chars());
+            let parsed = parser.parse_string();
+            assert!(parsed.is_err(), "should fail to parse {src:?}");
+
+            let err = parsed.unwrap_err();
+            assert_eq!(
+                (err.line(), err.column()),
+                pos,
+                "wrong position for {src:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn from_str_works() {
+        let value = from_str(" \n\t{\"a\": [1, true]} \r\n").unwrap();
+        let Value::Object(map) = value else {
+            panic!("should have parsed an object");
+        };
+        assert!(map.contains_key("a"));
+
+        let value: Value = "null".parse().unwrap();
+        assert_eq!(value, Value::Null);
+    }
+
+    #[test]
+    fn from_str_rejects_trailing_characters() {
+        let errors = [
+            ("true false", (1, 6)),
+            ("{\"a\":1}}", (1, 8)),
+            ("[1]\n  x", (2, 3)),
+            ("1 2", (1, 3)),
+            ("\"a\" \"b\"", (1, 5)),
+        ];
+        for (src, pos) in errors {
+            let err = from_str(src).unwrap_err();
+            assert_eq!(
+                (err.line(), err.column()),
+                pos,
+                "wrong position for {src:?}"
+            );
+        }
+
+        assert!(from_str("").is_err());
+        assert!(from_str("   ").is_err());
+        assert!(
+            from_str("\u{0C}1").is_err(),
+            "form feed is not json whitespace"
+        );
+    }
+
+    #[test]
+    fn from_slice_works() {
+        let src = "{\"clé\": [\"😀\", 1.5e3, null]}";
+        assert_eq!(from_slice(src.as_bytes()).unwrap(), from_str(src).unwrap());
+
+        let errors = [
+            (&b"[\"a\xffb\"]"[..], (1, 4, 3)),
+            (b"[\"\xc3\xa9\xc3\"]", (1, 4, 4)),
+            (b"\n  \"\xc0\x80\"", (2, 4, 4)),
+            (b"\"\xed\xa0\x80\"", (1, 2, 1)),
+            (b"[1, \xe2\x82", (1, 5, 4)),
+        ];
+        for (src, pos) in errors {
+            let err = from_slice(src).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidUtf8, "{src:?}");
+            assert_eq!((err.line(), err.column(), err.offset()), pos, "{src:?}");
+        }
+    }
+
+    #[test]
+    fn from_reader_works() {
+        let src = format!("[{}]", vec!["\"é€😀\""; 5000].join(",\n"));
+        let value = from_reader(src.as_bytes()).unwrap();
+        assert_eq!(value, from_str(&src).unwrap());
+
+        let err = from_reader(&b"{\"a\": \"\xff\"}"[..]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidUtf8);
+        assert_eq!(err.offset(), 7);
+
+        let mut parser = JsonParser::from_reader(&b"1 [2] {}"[..]);
+        assert_eq!(parser.parse().unwrap(), Value::Number(Number::from(1)));
+        assert!(parser.parse().unwrap().into_array().is_some());
+        assert!(parser.parse().unwrap().into_object().is_some());
+        assert!(parser.end().is_ok());
+    }
+
+    #[test]
+    fn from_reader_reports_io_errors() {
+        struct Broken(usize);
+
+        impl io::Read for Broken {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                if self.0 == 0 {
+                    return Err(io::Error::new(io::ErrorKind::ConnectionReset, "reset"));
+                }
+                self.0 -= 1;
+                buf[0] = b'[';
+                Ok(1)
+            }
+        }
+
+        let err = from_reader(Broken(3)).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Io);
+        assert_eq!(err.column(), 4);
+        let io_err = err.io_error().unwrap();
+        assert_eq!(io_err.kind(), io::ErrorKind::ConnectionReset);
+        assert!(std::error::Error::source(&err).is_some());
+    }
+
+    #[test]
+    fn parse_multiple_documents_works() {
+        let mut parser = JsonParser::new("1 [2]\n{\"a\": 3}  ".chars());
+        assert_eq!(parser.parse().unwrap(), Value::Number(Number::from(1)));
+        assert_eq!(
+            parser.parse().unwrap(),
+            Value::Array(vec![Value::Number(Number::from(2))].into())
+        );
+        assert!(matches!(parser.parse().unwrap(), Value::Object(_)));
+        assert!(parser.end().is_ok());
+    }
+
+    #[test]
+    fn parse_array_works() {
+        let src = r#"[1, 1.0, true, false, null, "name", "hironha", "123", ["nested_array"]]"#;
+        let mut parser = JsonParser::new(src.chars());
+        let parsed = parser.parse();
+        assert!(parsed.is_ok(), "should be able to parse array");
+
+        let array = parsed.unwrap();
+        let Value::Array(arr) = array else {
+            panic!("should have parsed an array");
+        };
+        let mut iter = arr.into_iter();
+        assert_eq!(iter.next(), Some(Value::Number(Number::from(1))));
+        assert_eq!(iter.next(), Some(Value::Number(Number::from(1.0))));
+        assert_eq!(iter.next(), Some(Value::Bool(true)));
+        assert_eq!(iter.next(), Some(Value::Bool(false)));
+        assert_eq!(iter.next(), Some(Value::Null));
+        assert_eq!(iter.next(), Some(Value::String(String::from("name"))));
+        assert_eq!(iter.next(), Some(Value::String(String::from("hironha"))));
+        assert_eq!(iter.next(), Some(Value::String(String::from("123"))));
+
+        let Value::Array(nested) = iter.next().unwrap() else {
+            panic!("should have parsed a nested array");
+        };
+        let mut nested_iter = nested.into_iter();
+        assert_eq!(
+            nested_iter.next(),
+            Some(Value::String(String::from("nested_array")))
+        );
+    }
+
+    #[test]
+    fn parse_object_works() {
+        let src = r#"{
+            "name": "test",
+            "wife": null,
+            "age": 23,
+            "happy": false,
+            "weight": 56.50,
+            "traits": ["male", "nerd"],
+            "pets": {
+                "name": "nina"
+            }
+        }"#
+        .trim();
+        let mut parser = JsonParser::new(src.chars());
+        let parsed = parser.parse();
+        if let Err(ref err) = parsed {
+            println!("{err}");
+        }
+        assert!(parsed.is_ok(), "should be able to parse object");
+
+        let Value::Object(map) = parsed.unwrap() else {
+            panic!("should have parsed an object");
+        };
+        let name = map.get("name").unwrap().clone();
+        assert_eq!(name, Value::String(String::from("test")));
+
+        let wife = map.get("wife").unwrap().clone();
+        assert_eq!(wife, Value::Null);
+
+        let age = map.get("age").unwrap().clone();
+        assert_eq!(age, Value::Number(Number::from(23)));
+
+        let happy = map.get("happy").unwrap().clone();
+        assert_eq!(happy, Value::Bool(false));
+
+        let weight = map.get("weight").unwrap().clone();
+        assert_eq!(weight, Value::Number(Number::from(56.50)));
+
+        let Value::Array(traits) = map.get("traits").unwrap().clone() else {
+            panic!("traits should be an array");
+        };
+        let mut traits = traits.into_iter();
+        assert_eq!(traits.next().unwrap(), Value::String(String::from("male")));
+        assert_eq!(traits.next().unwrap(), Value::String(String::from("nerd")));
+        assert!(traits.next().is_none());
+
+        let Value::Object(pets) = map.get("pets").unwrap().clone() else {
+            panic!("pets should be an object");
+        };
+        let pet_name = pets.get("name").unwrap().clone();
+        assert_eq!(pet_name, Value::String(String::from("nina")));
+    }
+
+    #[test]
+    fn error_kinds_works() {
+        let errors = [
+            ("[1, 2", ErrorKind::UnexpectedEof, Some("',' or ']'"), None),
+            ("", ErrorKind::UnexpectedEof, Some("value"), None),
+            ("nul", ErrorKind::UnexpectedEof, Some("null"), None),
+            (
+                "[1 2]",
+                ErrorKind::UnexpectedChar,
+                Some("',' or ']'"),
+                Some('2'),
+            ),
+            (
+                "{\"a\" 1}",
+                ErrorKind::UnexpectedChar,
+                Some("':'"),
+                Some('1'),
+            ),
+            (
+                "{1: 2}",
+                ErrorKind::UnexpectedChar,
+                Some("string"),
+                Some('1'),
+            ),
+            (
+                "{\"a\": 1 \"b\"}",
+                ErrorKind::UnexpectedChar,
+                Some("',' or '}'"),
+                Some('"'),
+            ),
+            ("trve", ErrorKind::UnexpectedChar, Some("true"), Some('v')),
+            ("?", ErrorKind::UnexpectedChar, Some("value"), Some('?')),
+            ("01", ErrorKind::InvalidNumber, None, None),
+            ("1.x", ErrorKind::InvalidNumber, Some("digit"), Some('x')),
+            ("1e400", ErrorKind::NumberOutOfRange, None, None),
+            ("\"\\q\"", ErrorKind::InvalidEscape, None, Some('q')),
+            (
+                "\"\\u12x4\"",
+                ErrorKind::InvalidEscape,
+                Some("hexadecimal digit"),
+                Some('x'),
+            ),
+            ("\"\\uDC00\"", ErrorKind::InvalidUnicode, None, None),
+            (
+                "\"\\uD800\"",
+                ErrorKind::InvalidUnicode,
+                Some("trailing surrogate"),
+                None,
+            ),
+            ("\"\t\"", ErrorKind::ControlCharacter, None, Some('\t')),
+            ("1 2", ErrorKind::TrailingCharacters, None, Some('2')),
+        ];
+        for (src, kind, expected, found) in errors {
+            let err = from_str(src).unwrap_err();
+            assert_eq!(err.kind(), kind, "wrong kind for {src:?}");
+            assert_eq!(err.expected(), expected, "wrong expected token for {src:?}");
+            assert_eq!(err.found(), found, "wrong found character for {src:?}");
+        }
+    }
+
+    #[test]
+    fn error_position_works() {
+        let err = from_str("{\n  \"é\": tru }").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedChar);
+        assert_eq!(err.line(), 2);
+        assert_eq!(err.column(), 11);
+        assert_eq!(err.offset(), 13);
+        assert_eq!(
+            err.message(),
+            "failed parsing true - expected character 'e' but received ' '"
+        );
+    }
+
+    #[test]
+    fn parse_rejects_trailing_commas() {
+        for src in ["[1,]", "{\"a\": 1,}", "[1, 2, ]"] {
+            let err = from_str(src).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::UnexpectedChar, "{src}");
+        }
+    }
+
+    #[test]
+    fn parse_depth_limit_works() {
+        let src = format!(
+            "{}{}",
+            "[".repeat(DEFAULT_MAX_DEPTH),
+            "]".repeat(DEFAULT_MAX_DEPTH)
+        );
+        assert!(from_str(&src).is_ok(), "default depth should be allowed");
+
+        let src = format!("{}1{}", "[{\"a\":".repeat(65), "}]".repeat(65));
+        let err = from_str(&src).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::DepthLimit);
+        assert_eq!(err.column(), 6 * 64 + 1);
+
+        let mut parser = JsonParser::new("[[[1]]]".chars()).max_depth(2);
+        let err = parser.parse().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::DepthLimit);
+        assert_eq!(err.column(), 3);
+
+        let mut parser = JsonParser::new("[[1]]".chars()).max_depth(2);
+        assert!(parser.parse().is_ok());
+    }
+
+    #[test]
+    fn deeply_nested_values_do_not_overflow() {
+        let depth = 200_000;
+        let mixed = format!("{}null{}", "[{\"a\":".repeat(depth), "}]".repeat(depth));
+        let arrays = format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        for src in [mixed, arrays] {
+            let value = JsonParser::new(src.chars())
+                .max_depth(usize::MAX)
+                .parse()
+                .unwrap();
+
+            let out = format::Formatter::new().format(&value).unwrap();
+            assert_eq!(out, src);
+            drop(value);
+        }
+    }
+
+    #[test]
+    fn parse_limits_works() {
+        use ErrorKind::*;
+
+        let parser = |src: &'static str| JsonParser::new(src.chars());
+        let cases = [
+            (parser("[1, 2]").max_input_len(5), Some((InputTooLong, 6))),
+            (parser("[1, 2]").max_input_len(6), None),
+            (
+                parser(r#"["abcd"]"#).max_string_len(3),
+                Some((StringTooLong, 2)),
+            ),
+            (
+                parser(r#"{"abcd": 1}"#).max_string_len(3),
+                Some((StringTooLong, 2)),
+            ),
+            (
+                parser(r#"["\u00e9\u00e9"]"#).max_string_len(3),
+                Some((StringTooLong, 2)),
+            ),
+            (parser(r#"["abc"]"#).max_string_len(3), None),
+            (
+                parser("[1.2345]").max_number_len(5),
+                Some((NumberTooLong, 2)),
+            ),
+            (
+                parser("[-1e10]").max_number_len(4),
+                Some((NumberTooLong, 2)),
+            ),
+            (parser("[1.234]").max_number_len(5), None),
+            (
+                parser("[1, 2,  3]").max_array_len(2),
+                Some((TooManyElements, 9)),
+            ),
+            (parser("[ 1]").max_array_len(0), Some((TooManyElements, 3))),
+            (parser("[1, 2]").max_array_len(2), None),
+            (
+                parser(r#"{"a": 1, "b": 2}"#).max_object_len(1),
+                Some((TooManyMembers, 10)),
+            ),
+            (parser(r#"{"a": 1}"#).max_object_len(1), None),
+        ];
+        for (mut parser, expected) in cases {
+            let result = parser.parse();
+            match expected {
+                Some((kind, col)) => {
+                    let err = result.unwrap_err();
+                    assert_eq!((err.kind(), err.column()), (kind, col), "{err}");
+                }
+                None => assert!(result.is_ok(), "{:?}", result.unwrap_err()),
+            }
+        }
+    }
+
+    fn parse_with(src: &str, duplicate_keys: DuplicateKeys) -> Result<Value, JsonParserError> {
+        let mut parser = JsonParser::new(src.chars()).duplicate_keys(duplicate_keys);
+        parser.parse()
+    }
+
+    #[test]
+    fn duplicate_keys_last_wins_works() {
+        let value = parse_with(r#"{"a": 1, "b": 2, "a": 3}"#, DuplicateKeys::LastWins).unwrap();
+        let map = value.into_object().unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map["a"].as_i64(), Some(3));
+    }
+
+    #[test]
+    fn duplicate_keys_first_wins_works() {
+        let value = parse_with(r#"{"a": 1, "b": 2, "a": 3}"#, DuplicateKeys::FirstWins).unwrap();
+        let map = value.into_object().unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map["a"].as_i64(), Some(1));
+    }
+
+    #[test]
+    fn duplicate_keys_error_works() {
+        let src = "{\n  \"a\": 1,\n  \"b\": {\"a\": 2},\n  \"a\": 3\n}";
+        let err = parse_with(src, DuplicateKeys::Error).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::DuplicateKey);
+        assert_eq!((err.line(), err.column()), (4, 3));
+
+        let first = err.first_occurrence().unwrap();
+        assert_eq!((first.line(), first.column(), first.offset()), (2, 3, 4));
+        assert_eq!(
+            err.message(),
+            "duplicate object key 'a', first defined at line 2 column 3"
+        );
+
+        let value = parse_with(r#"{"a": {"a": 1}, "b": [{"a": 2}]}"#, DuplicateKeys::Error);
+        assert!(
+            value.is_ok(),
+            "same key in nested objects is not a duplicate"
+        );
+    }
+
+    #[test]
+    fn duplicate_keys_collect_works() {
+        let src = r#"{"a": [1], "b": 2, "a": 3, "c": [4], "a": {"x": 5}}"#;
+        let value = parse_with(src, DuplicateKeys::Collect).unwrap();
+        let map = value.into_object().unwrap();
+        assert_eq!(map["b"].as_i64(), Some(2));
+        assert_eq!(map["c"], from_str("[4]").unwrap());
+        assert_eq!(map["a"], from_str(r#"[[1], 3, {"x": 5}]"#).unwrap());
+    }
+}
@@ -0,0 +1,247 @@
+use std::fmt;
+use std::iter::FromIterator;
+use std::mem;
+use std::ops::Index;
+
+#[cfg(not(feature = "preserve_order"))]
+use std::collections::{BTreeMap, btree_map};
+
+#[cfg(feature = "preserve_order")]
+use super::ordered::{self, OrderedMap};
+use super::{Value, drop_nested};
+
+#[cfg(not(feature = "preserve_order"))]
+type MapImpl = BTreeMap<String, Value>;
+#[cfg(not(feature = "preserve_order"))]
+type IterImpl<'a> = btree_map::Iter<'a, String, Value>;
+#[cfg(not(feature = "preserve_order"))]
+type IntoIterImpl = btree_map::IntoIter<String, Value>;
+
+#[cfg(feature = "preserve_order")]
+type MapImpl = OrderedMap<String, Value>;
+#[cfg(feature = "preserve_order")]
+type IterImpl<'a> = ordered::Iter<'a, String, Value>;
+#[cfg(feature = "preserve_order")]
+type IntoIterImpl = ordered::IntoIter<String, Value>;
+
+/// The members of a json object.
+///
+/// By default members are kept sorted by key. With the `preserve_order` feature they are
+/// kept in insertion order instead, which for parsed objects is the order of the document.
+#[derive(Clone, Default)]
+pub struct Map {
+    map: MapImpl,
+}
+
+impl Map {
+    pub fn new() -> Self {
+        Self {
+            map: MapImpl::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.map.get(key)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        self.map.get_mut(key)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Inserts a member, returning the previous value of `key`. Replacing the value of an
+    /// existing key keeps its original position.
+    pub fn insert(&mut self, key: String, value: Value) -> Option<Value> {
+        self.map.insert(key, value)
+    }
+
+    /// Removes a member, keeping the relative order of the remaining ones.
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        self.map.remove(key)
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            iter: self.map.iter(),
+        }
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &Value> {
+        self.iter().map(|(_, v)| v)
+    }
+}
+
+impl Drop for Map {
+    /// Drops nested arrays and objects iteratively, so dropping a deeply nested value
+    /// cannot overflow the thread stack.
+    fn drop(&mut self) {
+        if self.values().any(Value::is_container) {
+            drop_nested(mem::take(self).into_iter().map(|(_, v)| v).collect());
+        }
+    }
+}
+
+impl PartialEq for Map {
+    /// Two maps are equal when they have the same members, regardless of their order.
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+
+impl fmt::Debug for Map {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl Index<&str> for Map {
+    type Output = Value;
+
+    fn index(&self, key: &str) -> &Value {
+        self.get(key).expect("key should be present in the map")
+    }
+}
+
+impl FromIterator<(String, Value)> for Map {
+    fn from_iter<I: IntoIterator<Item = (String, Value)>>(iter: I) -> Self {
+        let mut map = Map::new();
+        for (k, v) in iter {
+            map.insert(k, v);
+        }
+        map
+    }
+}
+
+impl<'a> IntoIterator for &'a Map {
+    type Item = (&'a String, &'a Value);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+impl IntoIterator for Map {
+    type Item = (String, Value);
+    type IntoIter = IntoIter;
+
+    fn into_iter(mut self) -> IntoIter {
+        IntoIter {
+            iter: mem::take(&mut self.map).into_iter(),
+        }
+    }
+}
+
+pub struct Iter<'a> {
+    iter: IterImpl<'a>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a String, &'a Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+pub struct IntoIter {
+    iter: IntoIterImpl,
+}
+
+impl Iterator for IntoIter {
+    type Item = (String, Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_works() {
+        let mut map = Map::new();
+        assert!(map.is_empty());
+        assert_eq!(map.insert(String::from("b"), Value::Null), None);
+        assert_eq!(map.insert(String::from("a"), Value::Bool(true)), None);
+        assert_eq!(
+            map.insert(String::from("b"), Value::Bool(false)),
+            Some(Value::Null)
+        );
+        assert_eq!(map.len(), 2);
+        assert_eq!(map["b"], Value::Bool(false));
+        assert!(map.contains_key("a"));
+
+        *map.get_mut("a").unwrap() = Value::Null;
+        assert_eq!(map.get("a"), Some(&Value::Null));
+        assert_eq!(map.remove("a"), Some(Value::Null));
+        assert_eq!(map.remove("a"), None);
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn map_eq_ignores_order() {
+        let a: Map = [("x", 1), ("y", 2)]
+            .into_iter()
+            .map(|(k, v)| (String::from(k), Value::Number(v.into())))
+            .collect();
+        let b: Map = [("y", 2), ("x", 1)]
+            .into_iter()
+            .map(|(k, v)| (String::from(k), Value::Number(v.into())))
+            .collect();
+        assert_eq!(a, b);
+    }
+
+    #[cfg(not(feature = "preserve_order"))]
+    #[test]
+    fn map_iterates_in_key_order() {
+        let map: Map = ["b", "c", "a"]
+            .into_iter()
+            .map(|k| (String::from(k), Value::Null))
+            .collect();
+        let keys: Vec<_> = map.keys().map(String::as_str).collect();
+        assert_eq!(keys, ["a", "b", "c"]);
+    }
+
+    #[cfg(feature = "preserve_order")]
+    #[test]
+    fn map_iterates_in_insertion_order() {
+        let mut map: Map = ["b", "c", "a"]
+            .into_iter()
+            .map(|k| (String::from(k), Value::Null))
+            .collect();
+        map.insert(String::from("c"), Value::Bool(true));
+        map.remove("b");
+        map.insert(String::from("b"), Value::Null);
+        let keys: Vec<_> = map.keys().map(String::as_str).collect();
+        assert_eq!(keys, ["c", "a", "b"]);
+
+        let keys: Vec<_> = map.into_iter().map(|(k, _)| k).collect();
+        assert_eq!(keys, ["c", "a", "b"]);
+    }
+}
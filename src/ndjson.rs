@@ -0,0 +1,444 @@
+//! Newline-delimited json, also known as JSON Lines: one value per line.
+//!
+//! ```
+//! use json::ndjson::{Reader, Writer};
+//!
+//! let mut writer = Writer::new(Vec::new());
+//! for record in Reader::new("{\"a\": 1}\r\n\n[true, null]".as_bytes()) {
+//!     writer.write(&record.unwrap()).unwrap();
+//! }
+//! assert_eq!(writer.into_inner().unwrap(), b"{\"a\":1}\n[true,null]\n");
+//! ```
+
+use std::io::{self, BufRead, Write};
+use std::{error, fmt};
+
+use super::format::Formatter;
+use super::{DuplicateKeys, ErrorKind, JsonParserError, Options, Position, SliceParser, Value};
+
+/// Reads one value per line from an [`io::Read`]. Lines holding only whitespace are
+/// skipped, lines may end with `\n` or `\r\n`, and the last one may have no line ending.
+///
+/// Iteration stops after an error reading the input. Invalid records are reported and
+/// iteration goes on with the next line, unless [`skip_invalid`](Self::skip_invalid)
+/// leaves them out.
+///
+/// Every record is parsed like a [`SliceParser`] would, with the limits and settings of
+/// the chained setters. Lines longer than [`max_input_len`](Self::max_input_len) fail
+/// without being buffered whole.
+pub struct Reader<R> {
+    reader: io::BufReader<R>,
+    line: Vec<u8>,
+    /// Where the next line starts.
+    pos: Position,
+    /// Number of records read so far, valid or not.
+    record: usize,
+    skip_invalid: bool,
+    skipped: usize,
+    options: Options,
+    done: bool,
+}
+
+impl<R: io::Read> Reader<R> {
+    /// Reads are buffered, so `reader` does not need to be buffered itself.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader: io::BufReader::new(reader),
+            line: Vec::new(),
+            pos: Position::start(),
+            record: 0,
+            skip_invalid: false,
+            skipped: 0,
+            options: Options::default(),
+            done: false,
+        }
+    }
+
+    /// Same as [`JsonParser::max_depth`](crate::JsonParser::max_depth), for every record.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.options.max_depth = max_depth;
+        self
+    }
+
+    /// Same as [`JsonParser::max_input_len`](crate::JsonParser::max_input_len), for every
+    /// record. Its line ending is not counted.
+    pub fn max_input_len(mut self, max_input_len: usize) -> Self {
+        self.options.max_input_len = max_input_len;
+        self
+    }
+
+    /// Same as [`JsonParser::max_string_len`](crate::JsonParser::max_string_len).
+    pub fn max_string_len(mut self, max_string_len: usize) -> Self {
+        self.options.max_string_len = max_string_len;
+        self
+    }
+
+    /// Same as [`JsonParser::max_number_len`](crate::JsonParser::max_number_len).
+    pub fn max_number_len(mut self, max_number_len: usize) -> Self {
+        self.options.max_number_len = max_number_len;
+        self
+    }
+
+    /// Same as [`JsonParser::max_array_len`](crate::JsonParser::max_array_len).
+    pub fn max_array_len(mut self, max_array_len: usize) -> Self {
+        self.options.max_array_len = max_array_len;
+        self
+    }
+
+    /// Same as [`JsonParser::max_object_len`](crate::JsonParser::max_object_len).
+    pub fn max_object_len(mut self, max_object_len: usize) -> Self {
+        self.options.max_object_len = max_object_len;
+        self
+    }
+
+    /// Same as [`JsonParser::duplicate_keys`](crate::JsonParser::duplicate_keys).
+    pub fn duplicate_keys(mut self, duplicate_keys: DuplicateKeys) -> Self {
+        self.options.duplicate_keys = duplicate_keys;
+        self
+    }
+
+    /// Same as [`JsonParser::arbitrary_precision`](crate::JsonParser::arbitrary_precision).
+    pub fn arbitrary_precision(mut self, arbitrary_precision: bool) -> Self {
+        self.options.arbitrary_precision = arbitrary_precision;
+        self
+    }
+
+    /// Leaves out records that are not valid json instead of reporting them, counting
+    /// them in [`skipped`](Self::skipped). Errors reading the input are still reported.
+    pub fn skip_invalid(mut self, skip_invalid: bool) -> Self {
+        self.skip_invalid = skip_invalid;
+        self
+    }
+
+    /// Number of invalid records left out so far with [`skip_invalid`](Self::skip_invalid).
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    /// Reads the next line into `line`, returning the number of bytes read. Only enough
+    /// of an overlong line is kept to fail on it, the rest is read and thrown away.
+    fn read_line(&mut self) -> io::Result<usize> {
+        // room for the longest record allowed, a `\r` and one byte past the limit
+        let cap = self.options.max_input_len.saturating_add(2);
+        let mut read = 0;
+        loop {
+            let available = match self.reader.fill_buf() {
+                Ok(available) => available,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+            let (len, done) = match available.iter().position(|&b| b == b'\n') {
+                Some(i) => (i + 1, true),
+                None => (available.len(), available.is_empty()),
+            };
+            let room = cap.saturating_sub(self.line.len());
+            self.line.extend_from_slice(&available[..len.min(room)]);
+            self.reader.consume(len);
+            read += len;
+            if done {
+                return Ok(read);
+            }
+        }
+    }
+}
+
+impl<R: io::Read> Iterator for Reader<R> {
+    type Item = Result<Value, RecordError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            self.line.clear();
+            let start = self.pos;
+            match self.read_line() {
+                Ok(0) => self.done = true,
+                Ok(len) => {
+                    self.pos.line += 1;
+                    self.pos.offset += len;
+                }
+                Err(err) => {
+                    self.done = true;
+                    let msg = format!("failed reading input: {err}");
+                    let error = JsonParserError::new(ErrorKind::Io, msg, start).with_io(err);
+                    return Some(Err(RecordError {
+                        record: self.record + 1,
+                        error,
+                    }));
+                }
+            }
+
+            // without its line ending, so a record that ends early fails on its own line
+            let line = self.line.strip_suffix(b"\n").unwrap_or(&self.line);
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            if line.iter().all(|b| matches!(b, b' ' | b'\t' | b'\r')) {
+                continue;
+            }
+            self.record += 1;
+            let mut parser = SliceParser::with_options(line, self.options.clone());
+            let value = parser
+                .parse()
+                .and_then(|value| parser.end().map(|()| value));
+            match value {
+                Ok(value) => return Some(Ok(value)),
+                Err(_) if self.skip_invalid => self.skipped += 1,
+                Err(err) => {
+                    return Some(Err(RecordError {
+                        record: self.record,
+                        error: err.relative_to(start),
+                    }));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// A record that could not be read, located in the whole input.
+#[derive(Clone, Debug)]
+pub struct RecordError {
+    record: usize,
+    error: JsonParserError,
+}
+
+impl RecordError {
+    /// Number of the record, starting at 1. Blank lines are not records.
+    pub fn record(&self) -> usize {
+        self.record
+    }
+
+    /// Line of the error in the whole input, starting at 1.
+    pub fn line(&self) -> u32 {
+        self.error.line()
+    }
+
+    /// The error, with its position in the whole input rather than in the record.
+    pub fn error(&self) -> &JsonParserError {
+        &self.error
+    }
+
+    pub fn into_error(self) -> JsonParserError {
+        self.error
+    }
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "record {}: {}", self.record, self.error)
+    }
+}
+
+impl error::Error for RecordError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Writes one value per line into an [`io::Write`], as compact [`Formatter`] output
+/// followed by a line ending. Writes are buffered and flushed when the writer is dropped
+/// or with [`flush`](Self::flush).
+pub struct Writer<W: io::Write> {
+    writer: io::BufWriter<W>,
+    formatter: Formatter,
+    /// The record being written, so a record that fails to format leaves no partial
+    /// line behind.
+    record: String,
+}
+
+impl<W: io::Write> Writer<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: io::BufWriter::new(writer),
+            formatter: Formatter::new().trailing_newline(true),
+            record: String::new(),
+        }
+    }
+
+    /// Formats records with the options of `formatter`, e.g. to escape non-ascii
+    /// characters or end lines with `\r\n`. Records are still written on a single line,
+    /// each followed by a line ending.
+    pub fn formatter(mut self, formatter: Formatter) -> Self {
+        self.formatter = formatter.compact().trailing_newline(true);
+        self
+    }
+
+    /// Writes `value` as the next record. A non-finite number with
+    /// [`NonFinite::Error`](crate::format::NonFinite::Error) fails with
+    /// [`io::ErrorKind::InvalidData`], and nothing is written.
+    pub fn write(&mut self, value: &Value) -> io::Result<()> {
+        self.record.clear();
+        if self.formatter.format_to(&mut self.record, value).is_err() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "non-finite numbers cannot be written as json",
+            ));
+        }
+        self.writer.write_all(self.record.as_bytes())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Flushes the records written so far and returns the underlying writer.
+    pub fn into_inner(self) -> io::Result<W> {
+        self.writer
+            .into_inner()
+            .map_err(io::IntoInnerError::into_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::NonFinite;
+    use crate::{Number, from_str};
+
+    fn records(src: &str) -> Vec<Result<Value, (usize, u32, u32, usize)>> {
+        Reader::new(src.as_bytes())
+            .map(|record| {
+                record.map_err(|err| {
+                    let pos = err.error().position();
+                    (err.record(), err.line(), pos.column(), pos.offset())
+                })
+            })
+            .collect()
+    }
+
+    /// Fails after returning its input.
+    struct Broken<'a>(&'a [u8]);
+
+    impl io::Read for Broken<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.0.is_empty() {
+                return Err(io::Error::other("connection reset"));
+            }
+            let len = self.0.len().min(buf.len());
+            buf[..len].copy_from_slice(&self.0[..len]);
+            self.0 = &self.0[len..];
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn reader_works() {
+        let src = "{\"a\": 1}\n\n   \n[1, 2]\r\n\r\n\t\"last\"";
+        let expected = [
+            from_str("{\"a\": 1}").unwrap(),
+            from_str("[1, 2]").unwrap(),
+            Value::String(String::from("last")),
+        ];
+        let values: Vec<_> = records(src).into_iter().map(Result::unwrap).collect();
+        assert_eq!(values, expected);
+
+        assert!(records("").is_empty());
+        assert!(records("\n\r\n  ").is_empty());
+        assert_eq!(records("1\r\n2\n").len(), 2);
+    }
+
+    #[test]
+    fn reader_reports_record_and_line() {
+        let src = "1\n\n{\"a\" 1}\r\n2\n[1,\n3\n\"x\" \"y\"";
+        let records = records(src);
+        assert_eq!(records.len(), 6);
+        assert_eq!(records[0], Ok(Value::Number(Number::from(1))));
+        assert_eq!(records[1], Err((2, 3, 6, 8)));
+        assert_eq!(records[2], Ok(Value::Number(Number::from(2))));
+        // a value cannot continue on the next line
+        assert_eq!(records[3], Err((4, 5, 4, 17)));
+        assert_eq!(records[4], Ok(Value::Number(Number::from(3))));
+        assert_eq!(records[5], Err((6, 7, 5, 24)));
+
+        let err = Reader::new(src.as_bytes()).nth(1).unwrap().unwrap_err();
+        assert_eq!(err.error().kind(), ErrorKind::UnexpectedChar);
+        assert_eq!(
+            err.to_string(),
+            "record 2: Parse json error at line 3 column 6: expected character ':' after an object key but received '1'"
+        );
+    }
+
+    #[test]
+    fn reader_skips_invalid_records() {
+        let src = "1\nnope\n2\n{\n3";
+        let mut reader = Reader::new(src.as_bytes()).skip_invalid(true);
+        let values: Vec<_> = reader.by_ref().map(Result::unwrap).collect();
+        assert_eq!(values, [1, 2, 3].map(|n| Value::Number(Number::from(n))));
+        assert_eq!(reader.skipped(), 2);
+    }
+
+    #[test]
+    fn reader_applies_limits() {
+        let long = format!("[{}1]", "1, ".repeat(10_000));
+        let src = format!("[1,2]\r\n{long}\n{{\"a\":1}}\n[[1]]\n");
+        let mut reader = Reader::new(src.as_bytes()).max_input_len(8).max_depth(1);
+        assert!(reader.next().unwrap().is_ok());
+        let err = reader.next().unwrap().unwrap_err();
+        assert_eq!(err.error().kind(), ErrorKind::InputTooLong);
+        let pos = err.error().position();
+        assert_eq!((err.line(), pos.column(), pos.offset()), (2, 9, 15));
+        // the long line was not buffered whole
+        assert!(reader.line.capacity() < 100);
+        assert!(reader.next().unwrap().is_ok());
+        let err = reader.next().unwrap().unwrap_err();
+        assert_eq!((err.record(), err.line()), (4, 4));
+        assert_eq!(err.error().kind(), ErrorKind::DepthLimit);
+        assert!(reader.next().is_none());
+
+        let src = "{\"a\": 1, \"a\": 2}";
+        let mut reader = Reader::new(src.as_bytes()).duplicate_keys(DuplicateKeys::Error);
+        let err = reader.next().unwrap().unwrap_err();
+        assert_eq!(err.error().kind(), ErrorKind::DuplicateKey);
+    }
+
+    #[test]
+    fn reader_stops_after_io_errors() {
+        let mut reader = Reader::new(Broken(b"1\n2\n")).skip_invalid(true);
+        assert!(reader.next().unwrap().is_ok());
+        assert!(reader.next().unwrap().is_ok());
+        let err = reader.next().unwrap().unwrap_err();
+        assert_eq!((err.record(), err.line()), (3, 3));
+        assert_eq!(err.error().kind(), ErrorKind::Io);
+        assert_eq!(
+            err.error().io_error().unwrap().to_string(),
+            "connection reset"
+        );
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn writer_works() {
+        let values = [
+            from_str("{\"a\": \"é\", \"b\": [1, 2]}").unwrap(),
+            Value::Null,
+            from_str("[]").unwrap(),
+        ];
+        let mut writer = Writer::new(Vec::new());
+        for value in &values {
+            writer.write(value).unwrap();
+        }
+        let out = writer.into_inner().unwrap();
+        assert_eq!(out, "{\"a\":\"é\",\"b\":[1,2]}\nnull\n[]\n".as_bytes());
+
+        let read: Vec<_> = Reader::new(&out[..]).map(Result::unwrap).collect();
+        assert_eq!(read, values);
+    }
+
+    #[test]
+    fn writer_formatter_works() {
+        let formatter = Formatter::standard()
+            .ensure_ascii(true)
+            .line_ending(crate::format::LineEnding::CrLf)
+            .non_finite(NonFinite::Error);
+        let mut writer = Writer::new(Vec::new()).formatter(formatter);
+        writer
+            .write(&from_str("[\"é\", {\"a\": 1}]").unwrap())
+            .unwrap();
+        let err = writer
+            .write(&Value::Array(
+                vec![Value::Number(Number::from(f64::NAN))].into(),
+            ))
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        writer.write(&Value::Bool(true)).unwrap();
+        let out = writer.into_inner().unwrap();
+        assert_eq!(out, b"[\"\\u00e9\",{\"a\": 1}]\r\ntrue\r\n");
+    }
+}
@@ -0,0 +1,454 @@
+use std::fmt::{self, Write};
+use std::str;
+
+/// A JSON number. Integers that fit in an `i64` or `u64` are stored exactly, everything
+/// else is stored as an `f64`, unless the number was created from its raw decimal text
+/// (see [`Number::from_raw`]), in which case that text is kept verbatim.
+#[derive(Clone, Debug)]
+pub struct Number {
+    n: N,
+}
+
+#[derive(Clone, Debug)]
+enum N {
+    /// Always greater than or equal to zero.
+    PosInt(u64),
+    /// Always less than zero.
+    NegInt(i64),
+    Float(f64),
+    /// Always a valid JSON number lexeme.
+    Raw(Box<str>),
+}
+
+impl Number {
+    pub fn from_f64(f: f64) -> Self {
+        Self { n: N::Float(f) }
+    }
+
+    /// Creates a number that keeps `raw` exactly as written, so it is never rounded.
+    /// Returns `None` if `raw` is not a valid JSON number.
+    pub fn from_raw(raw: &str) -> Option<Self> {
+        Decimal::parse(raw)?;
+        Some(Self {
+            n: N::Raw(raw.into()),
+        })
+    }
+
+    /// Returns the original decimal text if the number was created with [`Number::from_raw`].
+    pub fn as_raw(&self) -> Option<&str> {
+        match &self.n {
+            N::Raw(raw) => Some(raw),
+            _ => None,
+        }
+    }
+
+    /// The value if the number is stored as an `f64`, i.e. neither an integer nor raw.
+    pub(crate) fn as_float(&self) -> Option<f64> {
+        match self.n {
+            N::Float(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn is_i64(&self) -> bool {
+        self.as_i64().is_some()
+    }
+
+    pub fn is_u64(&self) -> bool {
+        self.as_u64().is_some()
+    }
+
+    pub fn is_f64(&self) -> bool {
+        match &self.n {
+            N::PosInt(_) | N::NegInt(_) => false,
+            N::Float(_) => true,
+            N::Raw(_) => !self.is_i64() && !self.is_u64(),
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match &self.n {
+            N::PosInt(n) => i64::try_from(*n).ok(),
+            N::NegInt(n) => Some(*n),
+            N::Float(_) => None,
+            N::Raw(raw) => raw.parse().ok(),
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match &self.n {
+            N::PosInt(n) => Some(*n),
+            N::NegInt(_) | N::Float(_) => None,
+            N::Raw(raw) => raw.parse().ok(),
+        }
+    }
+
+    /// Converts the number to an `f64`, which may lose precision for integers above 2^53
+    /// and for raw numbers with more significant digits than an `f64` can hold.
+    pub fn as_f64(&self) -> f64 {
+        match &self.n {
+            N::PosInt(n) => *n as f64,
+            N::NegInt(n) => *n as f64,
+            N::Float(n) => *n,
+            N::Raw(raw) => raw.parse().expect("raw number should be a valid float"),
+        }
+    }
+
+    /// The decimal value of the number, or `None` for NaN and infinities. Integers and
+    /// floats are formatted into `buf`, floats with the shortest digits that read back
+    /// as the same float.
+    fn decimal<'a>(&'a self, buf: &'a mut Buf) -> Option<Decimal<'a>> {
+        let written = match &self.n {
+            N::PosInt(n) => write!(buf, "{n}"),
+            N::NegInt(n) => write!(buf, "{n}"),
+            N::Float(n) => write!(buf, "{n:e}"),
+            N::Raw(raw) => return Decimal::parse(raw),
+        };
+        written.ok()?;
+        let buf: &'a Buf = buf;
+        Decimal::parse(buf.as_str())
+    }
+}
+
+/// Numbers are equal when they have the same decimal value, whatever their
+/// representation, so `100`, `100.0` and the raw `1e2` are all equal. A float has the
+/// value of the shortest decimal that reads back as the same float, which is what the
+/// formatter writes: `0.1` equals the raw `0.1`, and a float above 2^53 may not equal
+/// the integer it converts to.
+impl PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.n, &other.n) {
+            (N::PosInt(a), N::PosInt(b)) => a == b,
+            (N::NegInt(a), N::NegInt(b)) => a == b,
+            (N::PosInt(_), N::NegInt(_)) | (N::NegInt(_), N::PosInt(_)) => false,
+            (N::Float(a), N::Float(b)) => a == b,
+            _ => {
+                let (mut a, mut b) = (Buf::default(), Buf::default());
+                let a = self.decimal(&mut a);
+                a.is_some() && a == other.decimal(&mut b)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.n {
+            N::PosInt(n) => n.fmt(f),
+            N::NegInt(n) => n.fmt(f),
+            // keep a fraction so the number reads back as a float, not an integer
+            N::Float(n) if n.is_finite() && n.fract() == 0.0 => write!(f, "{n:.1}"),
+            N::Float(n) => n.fmt(f),
+            N::Raw(raw) => raw.fmt(f),
+        }
+    }
+}
+
+/// A normalized decimal `(-1)^negative * digits * 10^exp`, where `digits` are the
+/// digits of `int` followed by those of `frac`, with neither leading nor trailing zeros.
+/// Zero is represented by empty `digits`.
+#[derive(Debug)]
+struct Decimal<'a> {
+    negative: bool,
+    int: &'a str,
+    frac: &'a str,
+    exp: i128,
+}
+
+impl<'a> Decimal<'a> {
+    /// Parses a JSON number lexeme, returning `None` if it does not follow the grammar.
+    fn parse(src: &'a str) -> Option<Self> {
+        let (negative, rest) = match src.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, src),
+        };
+
+        let int_len = rest.bytes().take_while(u8::is_ascii_digit).count();
+        let (int, rest) = rest.split_at(int_len);
+        if int.is_empty() || (int.len() > 1 && int.starts_with('0')) {
+            return None;
+        }
+
+        let (frac, rest) = match rest.strip_prefix('.') {
+            Some(rest) => {
+                let frac_len = rest.bytes().take_while(u8::is_ascii_digit).count();
+                if frac_len == 0 {
+                    return None;
+                }
+                rest.split_at(frac_len)
+            }
+            None => ("", rest),
+        };
+
+        let mut exp: i128 = 0;
+        if let Some(rest) = rest.strip_prefix(['e', 'E']) {
+            let (exp_negative, rest) = match rest.as_bytes().first() {
+                Some(b'-') => (true, &rest[1..]),
+                Some(b'+') => (false, &rest[1..]),
+                _ => (false, rest),
+            };
+            if rest.is_empty() || !rest.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            for b in rest.bytes() {
+                // saturating keeps absurd exponents comparable without overflowing
+                exp = exp.saturating_mul(10).saturating_add(i128::from(b - b'0'));
+            }
+            if exp_negative {
+                exp = -exp;
+            }
+        } else if !rest.is_empty() {
+            return None;
+        }
+        exp = exp.saturating_sub(frac.len() as i128);
+
+        // trailing zeros of the fraction come first, then those of the integer part
+        let (int, frac) = match frac.trim_end_matches('0') {
+            "" => {
+                let trimmed = int.trim_end_matches('0');
+                let zeros = frac.len() + int.len() - trimmed.len();
+                exp = exp.saturating_add(zeros as i128);
+                (trimmed, "")
+            }
+            trimmed => {
+                exp = exp.saturating_add((frac.len() - trimmed.len()) as i128);
+                (int, trimmed)
+            }
+        };
+        // only an integer part of `0` has leading zeros, which continue into the fraction
+        let (int, frac) = match int.trim_start_matches('0') {
+            "" => ("", frac.trim_start_matches('0')),
+            int => (int, frac),
+        };
+        if int.is_empty() && frac.is_empty() {
+            return Some(Self {
+                negative: false,
+                int,
+                frac,
+                exp: 0,
+            });
+        }
+
+        Some(Self {
+            negative,
+            int,
+            frac,
+            exp,
+        })
+    }
+
+    fn digits(&self) -> impl Iterator<Item = u8> + '_ {
+        self.int.bytes().chain(self.frac.bytes())
+    }
+}
+
+impl PartialEq for Decimal<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.negative == other.negative && self.exp == other.exp && self.digits().eq(other.digits())
+    }
+}
+
+/// Room for the longest integer or float a [`Number`] formats, so comparing numbers
+/// does not allocate.
+#[derive(Default)]
+struct Buf {
+    bytes: [u8; 32],
+    len: usize,
+}
+
+impl Buf {
+    fn as_str(&self) -> &str {
+        str::from_utf8(&self.bytes[..self.len]).unwrap_or_default()
+    }
+}
+
+impl Write for Buf {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        let dst = self.bytes.get_mut(self.len..end).ok_or(fmt::Error)?;
+        dst.copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+macro_rules! impl_from_unsigned {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for Number {
+                fn from(n: $ty) -> Self {
+                    Self { n: N::PosInt(n as u64) }
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_from_signed {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for Number {
+                fn from(n: $ty) -> Self {
+                    let n = if n < 0 {
+                        N::NegInt(n as i64)
+                    } else {
+                        N::PosInt(n as u64)
+                    };
+                    Self { n }
+                }
+            }
+        )*
+    };
+}
+
+impl_from_unsigned!(u8, u16, u32, u64, usize);
+impl_from_signed!(i8, i16, i32, i64, isize);
+
+impl From<f32> for Number {
+    fn from(n: f32) -> Self {
+        Self::from_f64(f64::from(n))
+    }
+}
+
+impl From<f64> for Number {
+    fn from(n: f64) -> Self {
+        Self::from_f64(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn number_accessors_works() {
+        let n = Number::from(u64::MAX);
+        assert_eq!(n.as_u64(), Some(u64::MAX));
+        assert_eq!(n.as_i64(), None);
+        assert!(n.is_u64() && !n.is_i64() && !n.is_f64());
+
+        let n = Number::from(i64::MIN);
+        assert_eq!(n.as_i64(), Some(i64::MIN));
+        assert_eq!(n.as_u64(), None);
+        assert!(n.is_i64() && !n.is_u64());
+
+        let n = Number::from(7u8);
+        assert_eq!(n.as_i64(), Some(7));
+        assert_eq!(n.as_u64(), Some(7));
+        assert_eq!(n.as_f64(), 7.0);
+
+        let n = Number::from(2.5);
+        assert_eq!(n.as_i64(), None);
+        assert_eq!(n.as_u64(), None);
+        assert_eq!(n.as_f64(), 2.5);
+        assert!(n.is_f64());
+    }
+
+    #[test]
+    fn number_display_works() {
+        assert_eq!(Number::from(u64::MAX).to_string(), "18446744073709551615");
+        assert_eq!(Number::from(i64::MIN).to_string(), "-9223372036854775808");
+        assert_eq!(Number::from(-3).to_string(), "-3");
+        assert_eq!(Number::from(1.5).to_string(), "1.5");
+        assert_eq!(Number::from(1.0).to_string(), "1.0");
+        assert_eq!(Number::from(-0.0).to_string(), "-0.0");
+        assert_eq!(Number::from(1e20).to_string(), "100000000000000000000.0");
+        assert_eq!(Number::from(f64::NAN).to_string(), "NaN");
+    }
+
+    #[test]
+    fn raw_number_works() {
+        let n = Number::from_raw("12345678901234567890.123456789012345678901").unwrap();
+        assert_eq!(n.to_string(), "12345678901234567890.123456789012345678901");
+        assert_eq!(
+            n.as_raw(),
+            Some("12345678901234567890.123456789012345678901")
+        );
+        assert_eq!(n.as_i64(), None);
+        assert!(n.is_f64());
+        assert_eq!(n.as_f64(), 12345678901234567890.123456789012345678901);
+
+        let n = Number::from_raw("-42").unwrap();
+        assert_eq!(n.as_i64(), Some(-42));
+        assert_eq!(n.as_u64(), None);
+        assert!(!n.is_f64());
+
+        for invalid in ["", "-", "01", "1.", ".5", "1e", "1e+", "+1", "1x", "NaN"] {
+            assert!(
+                Number::from_raw(invalid).is_none(),
+                "{invalid} should be invalid"
+            );
+        }
+    }
+
+    #[test]
+    fn raw_number_eq_is_lossless() {
+        let raw = |s| Number::from_raw(s).unwrap();
+        assert_eq!(raw("1.50"), raw("1.5"));
+        assert_eq!(raw("1.5e0"), raw("15e-1"));
+        assert_eq!(raw("0"), raw("-0.000"));
+        assert_eq!(raw("100"), raw("1e2"));
+        assert_eq!(raw("100"), Number::from(100));
+        assert_eq!(raw("-2.5"), Number::from(-2.5));
+        assert_ne!(raw("0.1000000000000000000001"), raw("0.1"));
+        assert_ne!(raw("9007199254740993"), raw("9007199254740992"));
+        assert_ne!(
+            raw("1e999999999999999999999"),
+            raw("1e999999999999999999998")
+        );
+        assert_ne!(raw("-1"), raw("1"));
+        assert_ne!(raw("1"), Number::from_f64(f64::NAN));
+    }
+
+    #[test]
+    fn number_eq_is_transitive() {
+        let raw = |s| Number::from_raw(s).unwrap();
+        let numbers = [
+            raw("100"),
+            raw("1.00e2"),
+            Number::from(100),
+            Number::from(100.0),
+            raw("0"),
+            raw("-0.0"),
+            Number::from(0),
+            Number::from(-0.0),
+            raw("0.1"),
+            Number::from(0.1),
+            raw("0.1000000000000000055511151231257827"),
+            Number::from(-7),
+            raw("-7e0"),
+            Number::from(-7.0),
+            Number::from(u64::MAX),
+            raw("18446744073709551615"),
+            Number::from(u64::MAX as f64),
+            raw("18446744073709552000"),
+            Number::from(1u64 << 60),
+            Number::from((1u64 << 60) as f64),
+            raw("1152921504606847e3"),
+            Number::from(f64::INFINITY),
+            Number::from(f64::NAN),
+        ];
+        for a in &numbers {
+            for b in &numbers {
+                assert_eq!(a == b, b == a, "{a} and {b}");
+                for c in &numbers {
+                    if a == b && b == c {
+                        assert_eq!(a, c, "{a} equals {b} and {b} equals {c}");
+                    }
+                }
+            }
+        }
+
+        assert_eq!(Number::from(100), Number::from(100.0));
+        assert_eq!(Number::from(-0.0), Number::from(0));
+        assert_eq!(Number::from(0.1), raw("0.1"));
+        assert_ne!(
+            Number::from(0.1),
+            raw("0.1000000000000000055511151231257827")
+        );
+        assert_eq!(Number::from(u64::MAX as f64), raw("18446744073709552000"));
+        assert_ne!(Number::from(u64::MAX as f64), Number::from(u64::MAX));
+        assert_eq!(Number::from(f64::INFINITY), Number::from(f64::INFINITY));
+        assert_ne!(Number::from(f64::NAN), Number::from(f64::NAN));
+    }
+}
@@ -0,0 +1,160 @@
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::{mem, slice, vec};
+
+/// A map that iterates in insertion order, backing [`crate::Map`] when the
+/// `preserve_order` feature is enabled.
+///
+/// Entries live in a `Vec` and a `HashMap` maps each key to its entry index, so lookups
+/// stay constant time. Removing shifts the following entries, which is linear.
+#[derive(Clone, Debug)]
+pub(crate) struct OrderedMap<K, V> {
+    entries: Vec<(K, V)>,
+    indices: HashMap<K, usize>,
+}
+
+impl<K: Hash + Eq + Clone, V> OrderedMap<K, V> {
+    pub(crate) fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            indices: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub(crate) fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = *self.indices.get(key)?;
+        Some(&self.entries[idx].1)
+    }
+
+    pub(crate) fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = *self.indices.get(key)?;
+        Some(&mut self.entries[idx].1)
+    }
+
+    pub(crate) fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.indices.contains_key(key)
+    }
+
+    pub(crate) fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(&idx) = self.indices.get(&key) {
+            return Some(mem::replace(&mut self.entries[idx].1, value));
+        }
+        self.indices.insert(key.clone(), self.entries.len());
+        self.entries.push((key, value));
+        None
+    }
+
+    pub(crate) fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = self.indices.remove(key)?;
+        let (_, value) = self.entries.remove(idx);
+        for (k, _) in &self.entries[idx..] {
+            *self
+                .indices
+                .get_mut::<K>(k)
+                .expect("every entry should be indexed") -= 1;
+        }
+        Some(value)
+    }
+
+    pub(crate) fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            iter: self.entries.iter(),
+        }
+    }
+}
+
+impl<K, V> Default for OrderedMap<K, V> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            indices: HashMap::new(),
+        }
+    }
+}
+
+impl<K, V> IntoIterator for OrderedMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> IntoIter<K, V> {
+        self.entries.into_iter()
+    }
+}
+
+pub(crate) struct Iter<'a, K, V> {
+    iter: slice::Iter<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(k, v)| (k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+pub(crate) type IntoIter<K, V> = vec::IntoIter<(K, V)>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ordered_map_keeps_insertion_order() {
+        let mut map = OrderedMap::new();
+        for (k, v) in [("z", 1), ("a", 2), ("m", 3)] {
+            map.insert(k, v);
+        }
+        assert_eq!(map.insert("a", 4), Some(2));
+        let entries: Vec<_> = map.iter().map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(entries, [("z", 1), ("a", 4), ("m", 3)]);
+    }
+
+    #[test]
+    fn ordered_map_remove_reindexes() {
+        let mut map = OrderedMap::new();
+        for (k, v) in [("a", 1), ("b", 2), ("c", 3), ("d", 4)] {
+            map.insert(k, v);
+        }
+        assert_eq!(map.remove("b"), Some(2));
+        assert_eq!(map.remove("b"), None);
+        assert_eq!(map.get("c"), Some(&3));
+        assert_eq!(map.get("d"), Some(&4));
+        *map.get_mut("d").unwrap() = 5;
+        assert_eq!(map.len(), 3);
+        assert!(!map.is_empty());
+        assert!(map.contains_key("a"));
+
+        let entries: Vec<_> = map.into_iter().collect();
+        assert_eq!(entries, [("a", 1), ("c", 3), ("d", 5)]);
+    }
+}
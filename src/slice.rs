@@ -0,0 +1,1169 @@
+use std::borrow::Cow;
+use std::str;
+
+use super::borrowed::{self, BorrowedValue};
+use super::source::decode;
+use super::{
+    DuplicateKeys, ErrorKind, Frame, JsonParserError, Number, ObjectFrame, Options, Position,
+    Value, duplicate_key, number_from_text,
+};
+
+/// A parser over UTF-8 bytes in memory that scans them by index instead of decoding one
+/// character at a time, which makes it faster than [`JsonParser`](crate::JsonParser).
+///
+/// It produces the same values and the same errors, positions included, as a
+/// [`JsonParser`](crate::JsonParser) over the same bytes with the same settings. Line and
+/// column numbers are only worked out when an error is reported.
+pub struct SliceParser<'a> {
+    src: &'a [u8],
+    index: usize,
+    options: Options,
+}
+
+impl<'a> SliceParser<'a> {
+    pub fn new(src: &'a [u8]) -> Self {
+        Self {
+            src,
+            index: 0,
+            options: Options::default(),
+        }
+    }
+
+    pub(crate) fn with_options(src: &'a [u8], options: Options) -> Self {
+        Self {
+            src,
+            index: 0,
+            options,
+        }
+    }
+
+    /// Same as [`JsonParser::max_depth`](crate::JsonParser::max_depth).
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.options.max_depth = max_depth;
+        self
+    }
+
+    /// Same as [`JsonParser::max_input_len`](crate::JsonParser::max_input_len).
+    pub fn max_input_len(mut self, max_input_len: usize) -> Self {
+        self.options.max_input_len = max_input_len;
+        self
+    }
+
+    /// Same as [`JsonParser::max_string_len`](crate::JsonParser::max_string_len).
+    pub fn max_string_len(mut self, max_string_len: usize) -> Self {
+        self.options.max_string_len = max_string_len;
+        self
+    }
+
+    /// Same as [`JsonParser::max_number_len`](crate::JsonParser::max_number_len).
+    pub fn max_number_len(mut self, max_number_len: usize) -> Self {
+        self.options.max_number_len = max_number_len;
+        self
+    }
+
+    /// Same as [`JsonParser::max_array_len`](crate::JsonParser::max_array_len).
+    pub fn max_array_len(mut self, max_array_len: usize) -> Self {
+        self.options.max_array_len = max_array_len;
+        self
+    }
+
+    /// Same as [`JsonParser::max_object_len`](crate::JsonParser::max_object_len).
+    pub fn max_object_len(mut self, max_object_len: usize) -> Self {
+        self.options.max_object_len = max_object_len;
+        self
+    }
+
+    /// Same as [`JsonParser::duplicate_keys`](crate::JsonParser::duplicate_keys).
+    pub fn duplicate_keys(mut self, duplicate_keys: DuplicateKeys) -> Self {
+        self.options.duplicate_keys = duplicate_keys;
+        self
+    }
+
+    /// Same as [`JsonParser::arbitrary_precision`](crate::JsonParser::arbitrary_precision).
+    pub fn arbitrary_precision(mut self, arbitrary_precision: bool) -> Self {
+        self.options.arbitrary_precision = arbitrary_precision;
+        self
+    }
+
+    /// Byte offset of the next unread byte.
+    pub fn offset(&self) -> usize {
+        self.index
+    }
+
+    /// Same as [`JsonParser::parse`](crate::JsonParser::parse).
+    pub fn parse(&mut self) -> Result<Value, JsonParserError> {
+        self.parse_with::<Owned>()
+    }
+
+    /// Like [`parse`](Self::parse), but strings and keys borrow from the input unless
+    /// they have escapes. See [`BorrowedValue`].
+    pub fn parse_borrowed(&mut self) -> Result<BorrowedValue<'a>, JsonParserError> {
+        self.parse_with::<Borrowed>()
+    }
+
+    fn parse_with<B: Builder<'a>>(&mut self) -> Result<B::Value, JsonParserError> {
+        let mut stack = Vec::<Frame<B::Value, B::Object>>::new();
+        loop {
+            self.skip_whitespace()?;
+            let mut value = match self.peek_byte() {
+                Some(b'[') => {
+                    self.enter(stack.len())?;
+                    self.skip_whitespace()?;
+                    if self.peek_byte() == Some(b']') {
+                        self.bump()?;
+                        B::array(Vec::new())
+                    } else {
+                        self.check_array_len(0)?;
+                        stack.push(Frame::Array(Vec::new()));
+                        continue;
+                    }
+                }
+                Some(b'{') => {
+                    self.enter(stack.len())?;
+                    self.skip_whitespace()?;
+                    if self.peek_byte() == Some(b'}') {
+                        self.bump()?;
+                        B::object(B::Object::default())
+                    } else {
+                        self.check_object_len(0)?;
+                        let mut object = B::Object::default();
+                        self.parse_member_key::<B>(&mut object)?;
+                        stack.push(Frame::Object(object));
+                        continue;
+                    }
+                }
+                _ => B::scalar(self.parse_scalar()?),
+            };
+
+            loop {
+                match stack.last_mut() {
+                    None => return Ok(value),
+                    Some(Frame::Array(values)) => {
+                        values.push(value);
+                        if self.parse_separator(']')? {
+                            self.check_array_len(values.len())?;
+                            break;
+                        }
+                        let Some(Frame::Array(values)) = stack.pop() else {
+                            unreachable!("last frame should be an array");
+                        };
+                        value = B::array(values);
+                    }
+                    Some(Frame::Object(object)) => {
+                        B::insert(object, value, self.options.duplicate_keys);
+                        if self.parse_separator('}')? {
+                            self.check_object_len(B::len(object))?;
+                            self.parse_member_key::<B>(object)?;
+                            break;
+                        }
+                        let Some(Frame::Object(object)) = stack.pop() else {
+                            unreachable!("last frame should be an object");
+                        };
+                        value = B::object(object);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Same as [`JsonParser::end`](crate::JsonParser::end).
+    pub fn end(&mut self) -> Result<(), JsonParserError> {
+        self.skip_whitespace()?;
+        match self.peek()? {
+            Some(ch) => {
+                let msg = format!("trailing characters after json value, received '{ch}'");
+                Err(self
+                    .error(ErrorKind::TrailingCharacters, msg)
+                    .with_found(ch))
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn parse_scalar(&mut self) -> Result<BorrowedValue<'a>, JsonParserError> {
+        match self.peek_byte() {
+            Some(b't') => return self.read_word("true").map(|_| BorrowedValue::Bool(true)),
+            Some(b'f') => return self.read_word("false").map(|_| BorrowedValue::Bool(false)),
+            Some(b'n') => return self.read_word("null").map(|_| BorrowedValue::Null),
+            Some(b'"') => return self.parse_str().map(BorrowedValue::String),
+            Some(b'-' | b'0'..=b'9') => return self.parse_number().map(BorrowedValue::Number),
+            _ => {}
+        }
+        match self.peek()? {
+            Some(ch) => {
+                let msg = format!("unexpected character '{ch}'");
+                Err(self.unexpected(ch, "value", msg))
+            }
+            None => Err(self.eof().with_expected("value")),
+        }
+    }
+
+    /// Works out the line and column of `offset`, which must be at a char boundary of
+    /// input that has already been validated.
+    pub(crate) fn position(&self, offset: usize) -> Position {
+        let before = &self.src[..offset];
+        let line_start = before
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |i| i + 1);
+        let lines = before[..line_start].iter().filter(|&&b| b == b'\n').count();
+        let chars = before[line_start..]
+            .iter()
+            .filter(|&&b| !is_continuation(b))
+            .count();
+        Position {
+            line: lines as u32 + 1,
+            col: chars as u32 + 1,
+            offset,
+        }
+    }
+
+    fn error_at(&self, kind: ErrorKind, msg: impl Into<String>, offset: usize) -> JsonParserError {
+        JsonParserError::new(kind, msg, self.position(offset))
+    }
+
+    fn error(&self, kind: ErrorKind, msg: impl Into<String>) -> JsonParserError {
+        self.error_at(kind, msg, self.index)
+    }
+
+    fn eof(&self) -> JsonParserError {
+        self.error(ErrorKind::UnexpectedEof, "unexpected end of input")
+    }
+
+    fn unexpected(&self, ch: char, expected: &'static str, msg: String) -> JsonParserError {
+        self.error(ErrorKind::UnexpectedChar, msg)
+            .with_expected(expected)
+            .with_found(ch)
+    }
+
+    fn input_too_long(&self, offset: usize) -> JsonParserError {
+        let msg = format!(
+            "input is longer than the maximum of {} bytes",
+            self.options.max_input_len
+        );
+        self.error_at(ErrorKind::InputTooLong, msg, offset)
+    }
+
+    /// Returns the next character without consuming it.
+    fn peek(&self) -> Result<Option<char>, JsonParserError> {
+        match self.src.get(self.index) {
+            None => Ok(None),
+            Some(&b) if b.is_ascii() => Ok(Some(char::from(b))),
+            Some(_) => match decode(&self.src[self.index..]) {
+                Ok(ch) => Ok(ch.map(|(ch, _)| ch)),
+                Err(_) => Err(self.error(ErrorKind::InvalidUtf8, "invalid UTF-8 in input")),
+            },
+        }
+    }
+
+    /// Returns the next byte without consuming it. Only ascii bytes can be used as chars
+    /// without going through [`peek`](Self::peek).
+    fn peek_byte(&self) -> Option<u8> {
+        self.src.get(self.index).copied()
+    }
+
+    /// Consumes an ascii byte returned by [`peek_byte`](Self::peek_byte).
+    fn bump(&mut self) -> Result<(), JsonParserError> {
+        self.advance(self.index + 1)
+    }
+
+    fn eat(&mut self) -> Result<char, JsonParserError> {
+        let Some(ch) = self.peek()? else {
+            return Err(self.eof());
+        };
+        self.advance(self.index + ch.len_utf8())?;
+        Ok(ch)
+    }
+
+    /// Consumes the already validated input up to `end`, failing at the first character
+    /// that goes past the maximum input length.
+    fn advance(&mut self, end: usize) -> Result<(), JsonParserError> {
+        if end > self.options.max_input_len {
+            return Err(self.input_too_long(self.char_start(self.options.max_input_len)));
+        }
+        self.index = end;
+        Ok(())
+    }
+
+    /// Start of the character holding the byte at `offset`.
+    fn char_start(&self, mut offset: usize) -> usize {
+        while is_continuation(self.src[offset]) {
+            offset -= 1;
+        }
+        offset
+    }
+
+    fn skip_whitespace(&mut self) -> Result<(), JsonParserError> {
+        let len = self.src[self.index..]
+            .iter()
+            .take_while(|&&b| matches!(b, b' ' | b'\t' | b'\n' | b'\r'))
+            .count();
+        self.advance(self.index + len)?;
+        // like `JsonParser`, invalid UTF-8 right after whitespace fails here
+        if self.peek_byte().is_some_and(|b| !b.is_ascii()) {
+            self.peek()?;
+        }
+        Ok(())
+    }
+
+    fn read_word(&mut self, word: &'static str) -> Result<(), JsonParserError> {
+        let end = self.index + word.len();
+        if self.src.get(self.index..end) == Some(word.as_bytes())
+            && end <= self.options.max_input_len
+        {
+            self.index = end;
+            return Ok(());
+        }
+
+        for w in word.chars() {
+            let Some(ch) = self.peek()? else {
+                return Err(self.eof().with_expected(word));
+            };
+            if ch != w {
+                let msg =
+                    format!("failed parsing {word} - expected character '{w}' but received '{ch}'");
+                return Err(self.unexpected(ch, word, msg));
+            }
+            self.eat()?;
+        }
+        Ok(())
+    }
+
+    fn parse_number(&mut self) -> Result<Number, JsonParserError> {
+        let start = self.index;
+        // valid numbers within the limits are scanned in one go, anything else goes
+        // through the char by char path below to fail the same way `JsonParser` does
+        if let Some(end) = self.scan_number()
+            && end - start <= self.options.max_number_len
+            && end <= self.options.max_input_len
+            && self.src.get(end).is_none_or(u8::is_ascii)
+        {
+            self.index = end;
+            return self.number(start);
+        }
+
+        if let Some('-') = self.peek()? {
+            self.read_number_char(start)?;
+        }
+
+        let zero = self.index;
+        let first = self.read_digit(start)?;
+        if first == '0' {
+            if let Some('0'..='9') = self.peek()? {
+                let msg = "leading zeros are not allowed in numbers";
+                return Err(self.error_at(ErrorKind::InvalidNumber, msg, zero));
+            }
+        } else {
+            self.read_digits(start)?;
+        }
+
+        if let Some('.') = self.peek()? {
+            self.read_number_char(start)?;
+            self.read_digit(start)?;
+            self.read_digits(start)?;
+        }
+
+        if let Some('e' | 'E') = self.peek()? {
+            self.read_number_char(start)?;
+            if let Some('+' | '-') = self.peek()? {
+                self.read_number_char(start)?;
+            }
+            self.read_digit(start)?;
+            self.read_digits(start)?;
+        }
+        self.number(start)
+    }
+
+    /// End of the number at the current position, `None` if it is not valid.
+    fn scan_number(&self) -> Option<usize> {
+        let digits = |i: usize| {
+            i + self.src[i..]
+                .iter()
+                .take_while(|b| b.is_ascii_digit())
+                .count()
+        };
+        let mut i = self.index;
+        if self.src.get(i) == Some(&b'-') {
+            i += 1;
+        }
+        match self.src.get(i) {
+            Some(b'0') => i += 1,
+            Some(b'1'..=b'9') => i = digits(i + 1),
+            _ => return None,
+        }
+        if self.src.get(i).is_some_and(u8::is_ascii_digit) {
+            return None;
+        }
+        if self.src.get(i) == Some(&b'.') {
+            let end = digits(i + 1);
+            if end == i + 1 {
+                return None;
+            }
+            i = end;
+        }
+        if let Some(b'e' | b'E') = self.src.get(i) {
+            i += 1;
+            if let Some(b'+' | b'-') = self.src.get(i) {
+                i += 1;
+            }
+            let end = digits(i);
+            if end == i {
+                return None;
+            }
+            i = end;
+        }
+        Some(i)
+    }
+
+    /// Converts the number text from `start` to the current position.
+    fn number(&self, start: usize) -> Result<Number, JsonParserError> {
+        let text = str::from_utf8(&self.src[start..self.index]).expect("numbers should be ascii");
+        match number_from_text(text, self.options.arbitrary_precision) {
+            Some(number) => Ok(number),
+            None => {
+                let msg = format!("number '{text}' is out of range");
+                Err(self.error_at(ErrorKind::NumberOutOfRange, msg, start))
+            }
+        }
+    }
+
+    fn read_digit(&mut self, start: usize) -> Result<char, JsonParserError> {
+        match self.peek()? {
+            Some(ch) if ch.is_ascii_digit() => self.read_number_char(start),
+            Some(ch) => {
+                let msg = format!("expected a digit but received character '{ch}'");
+                Err(self
+                    .error(ErrorKind::InvalidNumber, msg)
+                    .with_expected("digit")
+                    .with_found(ch))
+            }
+            None => Err(self.eof().with_expected("digit")),
+        }
+    }
+
+    /// Consumes a run of digits of the number starting at `start`.
+    fn read_digits(&mut self, start: usize) -> Result<(), JsonParserError> {
+        let len = self.src[self.index..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        let end = self.index + len;
+        // the number length is checked before each digit is consumed, so it wins when
+        // both limits are reached at the same digit
+        let too_long = start
+            .saturating_add(self.options.max_number_len)
+            .max(self.index);
+        let too_much = self.options.max_input_len;
+        if too_long < end && too_long <= too_much {
+            return Err(self.number_too_long(start));
+        }
+        self.advance(end)?;
+        // the character after the digits is always peeked, even when it is invalid
+        self.peek()?;
+        Ok(())
+    }
+
+    fn read_number_char(&mut self, start: usize) -> Result<char, JsonParserError> {
+        if self.index - start >= self.options.max_number_len {
+            return Err(self.number_too_long(start));
+        }
+        self.eat()
+    }
+
+    fn number_too_long(&self, start: usize) -> JsonParserError {
+        let msg = format!(
+            "number is longer than the maximum of {} characters",
+            self.options.max_number_len
+        );
+        self.error_at(ErrorKind::NumberTooLong, msg, start)
+    }
+
+    /// Parses a string, which borrows from the input unless it has escapes.
+    fn parse_str(&mut self) -> Result<Cow<'a, str>, JsonParserError> {
+        let start = self.index;
+        debug_assert_eq!(
+            self.peek_byte(),
+            Some(b'"'),
+            "string should start with quotes"
+        );
+        self.bump()?;
+
+        let text = self.read_unescaped(0, start)?;
+        if self.peek_byte() == Some(b'"') {
+            self.bump()?;
+            return Ok(Cow::Borrowed(text));
+        }
+
+        let mut buf = String::from(text);
+        loop {
+            let pos = self.index;
+            match self.eat()? {
+                '"' => break,
+                '\\' => {
+                    let ch = self.parse_escape(pos)?;
+                    buf.push(ch);
+                }
+                ch if ch < '\u{20}' => {
+                    let msg = format!(
+                        "unescaped control character '\\u{:04X}' in string",
+                        u32::from(ch)
+                    );
+                    let err = self.error_at(ErrorKind::ControlCharacter, msg, pos);
+                    return Err(err.with_found(ch));
+                }
+                ch => buf.push(ch),
+            }
+
+            if buf.len() > self.options.max_string_len {
+                return Err(self.string_too_long(start));
+            }
+            let text = self.read_unescaped(buf.len(), start)?;
+            buf.push_str(text);
+        }
+
+        Ok(Cow::Owned(buf))
+    }
+
+    /// Consumes the run of characters that need no special handling, up to the next
+    /// quote, backslash, control character or invalid UTF-8, and returns it. `buf_len` is
+    /// the length of the string before the run.
+    fn read_unescaped(&mut self, buf_len: usize, start: usize) -> Result<&'a str, JsonParserError> {
+        let src = self.src;
+        let len = self.src[self.index..]
+            .iter()
+            .take_while(|&&b| b != b'"' && b != b'\\' && b >= 0x20)
+            .count();
+        let mut end = self.index + len;
+        let text = match str::from_utf8(&src[self.index..end]) {
+            Ok(text) => text,
+            Err(err) => {
+                // stop before the invalid bytes, `eat` reports them
+                end = self.index + err.valid_up_to();
+                str::from_utf8(&src[self.index..end]).expect("prefix should be valid UTF-8")
+            }
+        };
+
+        // the first character past either limit fails, the input limit first when it
+        // is the same character
+        let string_end = self
+            .index
+            .saturating_add(self.options.max_string_len - buf_len);
+        let too_long = (string_end < end).then(|| self.char_start(string_end));
+        let input_end = self.options.max_input_len;
+        let too_much = (input_end < end).then(|| self.char_start(input_end));
+        match (too_much, too_long) {
+            (Some(input), Some(string)) if input <= string => Err(self.input_too_long(input)),
+            (Some(input), None) => Err(self.input_too_long(input)),
+            (_, Some(_)) => Err(self.string_too_long(start)),
+            (None, None) => {
+                self.index = end;
+                Ok(text)
+            }
+        }
+    }
+
+    fn string_too_long(&self, start: usize) -> JsonParserError {
+        let msg = format!(
+            "string is longer than the maximum of {} bytes",
+            self.options.max_string_len
+        );
+        self.error_at(ErrorKind::StringTooLong, msg, start)
+    }
+
+    fn parse_escape(&mut self, start: usize) -> Result<char, JsonParserError> {
+        let ch = match self.eat()? {
+            '"' => '"',
+            '\\' => '\\',
+            '/' => '/',
+            'b' => '\u{08}',
+            'f' => '\u{0C}',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'u' => return self.parse_unicode_escape(start),
+            ch => {
+                let msg = format!("invalid escape sequence '\\{ch}'");
+                let err = self.error_at(ErrorKind::InvalidEscape, msg, start);
+                return Err(err.with_found(ch));
+            }
+        };
+        Ok(ch)
+    }
+
+    fn parse_unicode_escape(&mut self, start: usize) -> Result<char, JsonParserError> {
+        let code = self.parse_hex_code()?;
+        match code {
+            0xD800..=0xDBFF => {
+                if self.peek()? != Some('\\') {
+                    let msg = format!("lone leading surrogate '\\u{code:04X}' in string");
+                    let err = self.error_at(ErrorKind::InvalidUnicode, msg, start);
+                    return Err(err.with_expected("trailing surrogate"));
+                }
+
+                let low_start = self.index;
+                self.eat()?;
+                let ch = self.eat()?;
+                if ch != 'u' {
+                    let msg = format!(
+                        "expected trailing surrogate after '\\u{code:04X}' but received '\\{ch}'"
+                    );
+                    let err = self.error_at(ErrorKind::InvalidUnicode, msg, low_start);
+                    return Err(err.with_expected("trailing surrogate").with_found(ch));
+                }
+
+                let low = self.parse_hex_code()?;
+                if !(0xDC00..=0xDFFF).contains(&low) {
+                    let msg = format!(
+                        "expected trailing surrogate after '\\u{code:04X}' but received '\\u{low:04X}'"
+                    );
+                    let err = self.error_at(ErrorKind::InvalidUnicode, msg, low_start);
+                    return Err(err.with_expected("trailing surrogate"));
+                }
+
+                let scalar = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                Ok(char::from_u32(scalar).expect("surrogate pair should decode to a valid char"))
+            }
+            0xDC00..=0xDFFF => {
+                let msg = format!("lone trailing surrogate '\\u{code:04X}' in string");
+                Err(self.error_at(ErrorKind::InvalidUnicode, msg, start))
+            }
+            _ => Ok(char::from_u32(code).expect("non surrogate code point should be a valid char")),
+        }
+    }
+
+    fn parse_hex_code(&mut self) -> Result<u32, JsonParserError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let Some(ch) = self.peek()? else {
+                return Err(self.eof().with_expected("hexadecimal digit"));
+            };
+            let Some(digit) = ch.to_digit(16) else {
+                let msg = format!("expected a hexadecimal digit but received '{ch}'");
+                return Err(self
+                    .error(ErrorKind::InvalidEscape, msg)
+                    .with_expected("hexadecimal digit")
+                    .with_found(ch));
+            };
+            self.eat()?;
+            code = code * 16 + digit;
+        }
+        Ok(code)
+    }
+
+    fn enter(&mut self, depth: usize) -> Result<(), JsonParserError> {
+        if depth >= self.options.max_depth {
+            let msg = format!(
+                "maximum nesting depth of {} exceeded",
+                self.options.max_depth
+            );
+            return Err(self.error(ErrorKind::DepthLimit, msg));
+        }
+        self.bump()
+    }
+
+    fn check_array_len(&mut self, len: usize) -> Result<(), JsonParserError> {
+        if len < self.options.max_array_len {
+            return Ok(());
+        }
+        self.skip_whitespace()?;
+        let msg = format!(
+            "array has more than the maximum of {} elements",
+            self.options.max_array_len
+        );
+        Err(self.error(ErrorKind::TooManyElements, msg))
+    }
+
+    fn check_object_len(&mut self, len: usize) -> Result<(), JsonParserError> {
+        if len < self.options.max_object_len {
+            return Ok(());
+        }
+        self.skip_whitespace()?;
+        let msg = format!(
+            "object has more than the maximum of {} members",
+            self.options.max_object_len
+        );
+        Err(self.error(ErrorKind::TooManyMembers, msg))
+    }
+
+    fn parse_member_key<B: Builder<'a>>(
+        &mut self,
+        object: &mut B::Object,
+    ) -> Result<(), JsonParserError> {
+        let (key, offset) = self.parse_key()?;
+        let duplicate_keys = self.options.duplicate_keys;
+        if duplicate_keys == DuplicateKeys::Error
+            && let Some(first) = B::first_offset(object, &key)
+        {
+            return Err(self.duplicate_key(&key, first, offset));
+        }
+        B::set_key(object, key, offset, duplicate_keys);
+        Ok(())
+    }
+
+    fn duplicate_key(&self, key: &str, first: usize, offset: usize) -> JsonParserError {
+        duplicate_key(key, self.position(first), self.position(offset))
+    }
+
+    fn parse_key(&mut self) -> Result<(Cow<'a, str>, usize), JsonParserError> {
+        self.skip_whitespace()?;
+        let offset = self.index;
+        let key = match self.peek_byte() {
+            Some(b'"') => self.parse_str()?,
+            _ => match self.peek()? {
+                Some(ch) => {
+                    let msg = "expected object key to be a string";
+                    return Err(self.unexpected(ch, "string", String::from(msg)));
+                }
+                None => return Err(self.eof().with_expected("string")),
+            },
+        };
+
+        self.skip_whitespace()?;
+        if self.peek_byte() == Some(b':') {
+            self.bump()?;
+            return Ok((key, offset));
+        }
+        match self.peek()? {
+            Some(ch) => {
+                let msg = format!("expected character ':' after an object key but received '{ch}'");
+                Err(self.unexpected(ch, "':'", msg))
+            }
+            None => Err(self.eof().with_expected("':'")),
+        }
+    }
+
+    fn parse_separator(&mut self, end: char) -> Result<bool, JsonParserError> {
+        let expected = if end == ']' {
+            "',' or ']'"
+        } else {
+            "',' or '}'"
+        };
+        self.skip_whitespace()?;
+        match self.peek_byte() {
+            Some(b',') => return self.bump().map(|_| true),
+            Some(b) if char::from(b) == end => return self.bump().map(|_| false),
+            _ => {}
+        }
+        match self.peek()? {
+            Some(ch) => {
+                let msg = if end == ']' {
+                    format!(
+                        "expected either array value separator ',' or end of array character ']', but received '{ch}'"
+                    )
+                } else {
+                    format!(
+                        "expected either object key value separator ',' or end of character '}}', but received '{ch}'"
+                    )
+                };
+                Err(self.unexpected(ch, expected, msg))
+            }
+            None => Err(self.eof().with_expected(expected)),
+        }
+    }
+}
+
+/// How [`SliceParser`] builds the values it parses, so owned and borrowed values share
+/// the same grammar.
+trait Builder<'a> {
+    type Value;
+    /// An object that is still being parsed.
+    type Object: Default;
+
+    fn scalar(value: BorrowedValue<'a>) -> Self::Value;
+    fn array(values: Vec<Self::Value>) -> Self::Value;
+    fn object(object: Self::Object) -> Self::Value;
+    /// Number of members, counting duplicate keys once.
+    fn len(object: &Self::Object) -> usize;
+    /// Offset of the first occurrence of `key`, when it is already in the object.
+    fn first_offset(object: &Self::Object, key: &str) -> Option<usize>;
+    /// Sets the key of the next member, which starts at `offset`.
+    fn set_key(
+        object: &mut Self::Object,
+        key: Cow<'a, str>,
+        offset: usize,
+        duplicate_keys: DuplicateKeys,
+    );
+    /// Adds the member whose key was set last.
+    fn insert(object: &mut Self::Object, value: Self::Value, duplicate_keys: DuplicateKeys);
+}
+
+/// Builds a [`Value`], for [`SliceParser::parse`].
+struct Owned;
+
+impl<'a> Builder<'a> for Owned {
+    type Value = Value;
+    type Object = ObjectFrame<usize>;
+
+    fn scalar(value: BorrowedValue<'a>) -> Value {
+        value.into_owned()
+    }
+
+    fn array(values: Vec<Value>) -> Value {
+        Value::Array(values.into())
+    }
+
+    fn object(object: ObjectFrame<usize>) -> Value {
+        Value::Object(object.members)
+    }
+
+    fn len(object: &ObjectFrame<usize>) -> usize {
+        object.members.len()
+    }
+
+    fn first_offset(object: &ObjectFrame<usize>, key: &str) -> Option<usize> {
+        object.positions.get(key).copied()
+    }
+
+    fn set_key(
+        object: &mut ObjectFrame<usize>,
+        key: Cow<'a, str>,
+        offset: usize,
+        duplicate_keys: DuplicateKeys,
+    ) {
+        let key = key.into_owned();
+        if duplicate_keys == DuplicateKeys::Error {
+            object.positions.insert(key.clone(), offset);
+        }
+        object.key = key;
+    }
+
+    fn insert(object: &mut ObjectFrame<usize>, value: Value, duplicate_keys: DuplicateKeys) {
+        object.insert(value, duplicate_keys);
+    }
+}
+
+/// Builds a [`BorrowedValue`], for [`SliceParser::parse_borrowed`].
+struct Borrowed;
+
+impl<'a> Builder<'a> for Borrowed {
+    type Value = BorrowedValue<'a>;
+    type Object = borrowed::ObjectFrame<'a>;
+
+    fn scalar(value: BorrowedValue<'a>) -> BorrowedValue<'a> {
+        value
+    }
+
+    fn array(values: Vec<BorrowedValue<'a>>) -> BorrowedValue<'a> {
+        BorrowedValue::Array(values.into())
+    }
+
+    fn object(object: borrowed::ObjectFrame<'a>) -> BorrowedValue<'a> {
+        BorrowedValue::Object(object.members.into())
+    }
+
+    fn len(object: &borrowed::ObjectFrame<'a>) -> usize {
+        object.members.len()
+    }
+
+    fn first_offset(object: &borrowed::ObjectFrame<'a>, key: &str) -> Option<usize> {
+        object.indices.get(key).map(|&(_, first)| first)
+    }
+
+    fn set_key(
+        object: &mut borrowed::ObjectFrame<'a>,
+        key: Cow<'a, str>,
+        offset: usize,
+        _duplicate_keys: DuplicateKeys,
+    ) {
+        object.key = key;
+        object.offset = offset;
+    }
+
+    fn insert(
+        object: &mut borrowed::ObjectFrame<'a>,
+        value: BorrowedValue<'a>,
+        duplicate_keys: DuplicateKeys,
+    ) {
+        object.insert(value, duplicate_keys);
+    }
+}
+
+fn is_continuation(b: u8) -> bool {
+    b & 0xC0 == 0x80
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::JsonParser;
+
+    type Outcome = Result<Value, (ErrorKind, String, Option<char>, Position, Option<Position>)>;
+
+    fn outcome(result: Result<Value, JsonParserError>) -> Outcome {
+        result.map_err(|err| {
+            let expected = err.expected().map_or(String::new(), String::from);
+            (
+                err.kind(),
+                format!("{} / {expected}", err.message()),
+                err.found(),
+                err.position(),
+                err.first_occurrence(),
+            )
+        })
+    }
+
+    trait Outcomes {
+        fn parse(&mut self) -> Result<Value, JsonParserError>;
+        fn end(&mut self) -> Result<(), JsonParserError>;
+
+        /// Parses every document, checking for trailing input after each one.
+        fn outcomes(&mut self) -> Vec<Outcome> {
+            let mut outcomes = Vec::new();
+            for _ in 0..8 {
+                let value = outcome(self.parse());
+                let failed = value.is_err();
+                outcomes.push(value);
+                if failed {
+                    break;
+                }
+                let end = outcome(self.end().map(|_| Value::Null));
+                // only trailing characters leave the parser in a state worth going on from
+                let done = !matches!(&end, Err((ErrorKind::TrailingCharacters, ..)));
+                outcomes.push(end);
+                if done {
+                    break;
+                }
+            }
+            outcomes
+        }
+    }
+
+    impl<T: crate::Source> Outcomes for JsonParser<T> {
+        fn parse(&mut self) -> Result<Value, JsonParserError> {
+            JsonParser::parse(self)
+        }
+
+        fn end(&mut self) -> Result<(), JsonParserError> {
+            JsonParser::end(self)
+        }
+    }
+
+    impl Outcomes for SliceParser<'_> {
+        fn parse(&mut self) -> Result<Value, JsonParserError> {
+            SliceParser::parse(self)
+        }
+
+        fn end(&mut self) -> Result<(), JsonParserError> {
+            SliceParser::end(self)
+        }
+    }
+
+    /// Parses borrowed values, converted to owned ones to compare them.
+    struct Borrowed<'a>(SliceParser<'a>);
+
+    impl Outcomes for Borrowed<'_> {
+        fn parse(&mut self) -> Result<Value, JsonParserError> {
+            self.0.parse_borrowed().map(BorrowedValue::into_owned)
+        }
+
+        fn end(&mut self) -> Result<(), JsonParserError> {
+            self.0.end()
+        }
+    }
+
+    fn assert_same(src: &[u8], options: &Options) {
+        let mut slow = JsonParser::from_slice(src);
+        slow.options = options.clone();
+        let mut fast = SliceParser::new(src);
+        fast.options = options.clone();
+        let expected = slow.outcomes();
+        let src_lossy = String::from_utf8_lossy(src);
+        assert_eq!(fast.outcomes(), expected, "{src_lossy:?} with {options:?}");
+
+        let mut borrowed = Borrowed(SliceParser::new(src));
+        borrowed.0.options = options.clone();
+        assert_eq!(
+            borrowed.outcomes(),
+            expected,
+            "borrowed {src_lossy:?} with {options:?}"
+        );
+    }
+
+    const CORPUS: &[&str] = &[
+        "null",
+        " true ",
+        "false",
+        "nul",
+        "tru e",
+        "-",
+        "-0",
+        "0",
+        "01",
+        "-01",
+        "1.",
+        "1.5",
+        "-12.5e+3",
+        "1E-2",
+        "1e",
+        "1e400",
+        "-1e400",
+        "18446744073709551615",
+        "18446744073709551616",
+        "-9223372036854775809",
+        "123456789012345678901234567890.5",
+        r#""""#,
+        r#""abc""#,
+        r#""a\"b\\c\/d\b\f\n\r\t""#,
+        r#""é€😀""#,
+        r#""\ud83d""#,
+        r#""\ud83dx""#,
+        r#""\ud83d\n""#,
+        r#""\ud83dA""#,
+        r#""\ude00""#,
+        r#""\u12""#,
+        r#""\u12g4""#,
+        r#""\x""#,
+        "\"a\u{1}b\"",
+        "\"tab\there\"",
+        "\"é€😀 mixed ascii\"",
+        r#""unterminated"#,
+        "[]",
+        "[ ]",
+        "[1,2,3]",
+        "[1 2]",
+        "[1,]",
+        "[,1]",
+        "[[[]]]",
+        "[[1,[2,[3]]],[]]",
+        "{}",
+        r#"{"a":1}"#,
+        r#"{"a":1,"b":[true,{"c":null}]}"#,
+        r#"{"a" 1}"#,
+        r#"{a:1}"#,
+        r#"{'a':1}"#,
+        r#"{"a":1,}"#,
+        r#"{"a":1 "b":2}"#,
+        r#"{"a":1,"a":2,"b":3,"a":4}"#,
+        r#"{"k":{"k":1,"k":2},"k":[1]}"#,
+        "[1]x",
+        "1 2 [3] {\"a\":4}",
+        "\n\n  [1,\r\n   2,\n   bad]",
+        "\u{feff}1",
+        "[\"é\", é]",
+        "  \u{0C}1",
+        "",
+        "   ",
+    ];
+
+    #[test]
+    fn slice_parser_matches_json_parser() {
+        let options = Options::default();
+        for src in CORPUS {
+            assert_same(src.as_bytes(), &options);
+        }
+    }
+
+    #[test]
+    fn slice_parser_matches_json_parser_on_damaged_input() {
+        let options = Options::default();
+        for src in CORPUS {
+            let src = src.as_bytes();
+            for len in 0..src.len() {
+                assert_same(&src[..len], &options);
+            }
+            for i in 0..src.len() {
+                for b in [0xff, 0xc3, 0x80, 0x01, b'"', b'\\', b',', b'}', b'x', b'9'] {
+                    let mut damaged = src.to_vec();
+                    damaged[i] = b;
+                    assert_same(&damaged, &options);
+                }
+            }
+        }
+        for src in [
+            &b"[\"a\xffb\"]"[..],
+            b"1\xff",
+            b"[1,\xff]",
+            b"\"\xed\xa0\x80\"",
+            b"\xe2\x82",
+        ] {
+            assert_same(src, &options);
+        }
+    }
+
+    #[test]
+    fn slice_parser_matches_json_parser_with_options() {
+        for src in CORPUS {
+            let src = src.as_bytes();
+            let mut configs = Vec::new();
+            for limit in 0..=src.len() + 1 {
+                configs.push(Options {
+                    max_input_len: limit,
+                    ..Options::default()
+                });
+            }
+            for limit in 0..8 {
+                configs.push(Options {
+                    max_string_len: limit,
+                    ..Options::default()
+                });
+                configs.push(Options {
+                    max_number_len: limit,
+                    ..Options::default()
+                });
+                configs.push(Options {
+                    max_number_len: limit,
+                    max_input_len: limit + 1,
+                    ..Options::default()
+                });
+                configs.push(Options {
+                    max_string_len: limit,
+                    max_input_len: limit + 2,
+                    ..Options::default()
+                });
+            }
+            for limit in 0..4 {
+                configs.push(Options {
+                    max_depth: limit,
+                    ..Options::default()
+                });
+                configs.push(Options {
+                    max_array_len: limit,
+                    ..Options::default()
+                });
+                configs.push(Options {
+                    max_object_len: limit,
+                    ..Options::default()
+                });
+            }
+            for duplicate_keys in [
+                DuplicateKeys::Error,
+                DuplicateKeys::FirstWins,
+                DuplicateKeys::LastWins,
+                DuplicateKeys::Collect,
+            ] {
+                configs.push(Options {
+                    duplicate_keys,
+                    ..Options::default()
+                });
+            }
+            configs.push(Options {
+                arbitrary_precision: true,
+                ..Options::default()
+            });
+            for options in &configs {
+                assert_same(src, options);
+            }
+        }
+    }
+
+    #[test]
+    fn slice_parser_works() {
+        let mut parser = SliceParser::new(b"[1, \"two\"] {\"three\": 3.0}")
+            .max_depth(1)
+            .duplicate_keys(DuplicateKeys::Error);
+        let Some(arr) = parser.parse().unwrap().into_array() else {
+            panic!("should have parsed an array");
+        };
+        assert_eq!(arr[1], Value::String(String::from("two")));
+        assert_eq!(parser.offset(), 10);
+        let Some(map) = parser.parse().unwrap().into_object() else {
+            panic!("should have parsed an object");
+        };
+        assert_eq!(map["three"].as_f64(), Some(3.0));
+        assert!(parser.end().is_ok());
+
+        let err = SliceParser::new(b"[[1]]").max_depth(1).parse().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::DepthLimit);
+        assert_eq!((err.line(), err.column()), (1, 2));
+    }
+}
@@ -0,0 +1,298 @@
+use std::{fmt, io, str};
+
+/// Input that [`crate::JsonParser`] reads characters from.
+///
+/// Every `Iterator<Item = char>` is a source, and [`SliceRead`] and [`IoRead`] decode
+/// UTF-8 bytes as they are read.
+pub trait Source {
+    /// Returns the next character, or `None` at the end of the input.
+    fn next_char(&mut self) -> Result<Option<char>, SourceError>;
+}
+
+impl<T: Iterator<Item = char>> Source for T {
+    fn next_char(&mut self) -> Result<Option<char>, SourceError> {
+        Ok(self.next())
+    }
+}
+
+/// Why a [`Source`] could not produce the next character.
+#[derive(Debug)]
+pub enum SourceError {
+    /// The bytes at the current position are not valid UTF-8.
+    InvalidUtf8,
+    /// Reading the underlying input failed.
+    Io(io::Error),
+}
+
+impl fmt::Display for SourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourceError::InvalidUtf8 => f.write_str("invalid UTF-8"),
+            SourceError::Io(err) => err.fmt(f),
+        }
+    }
+}
+
+/// Decodes the first character of `bytes`, returning it with its length in bytes.
+/// `bytes` must hold the whole character unless it reaches the end of the input.
+pub(crate) fn decode(bytes: &[u8]) -> Result<Option<(char, usize)>, SourceError> {
+    match bytes.first() {
+        None => Ok(None),
+        Some(&b) if b.is_ascii() => Ok(Some((char::from(b), 1))),
+        Some(_) => {
+            let bytes = &bytes[..bytes.len().min(4)];
+            // the first character is valid exactly when validation gets past it
+            let valid = match str::from_utf8(bytes) {
+                Ok(s) => s,
+                Err(err) => str::from_utf8(&bytes[..err.valid_up_to()])
+                    .expect("bytes up to valid_up_to should be valid UTF-8"),
+            };
+            let ch = valid.chars().next().ok_or(SourceError::InvalidUtf8)?;
+            Ok(Some((ch, ch.len_utf8())))
+        }
+    }
+}
+
+/// A [`Source`] over UTF-8 bytes in memory, validated a run at a time as they are read.
+#[derive(Clone, Debug)]
+pub struct SliceRead<'a> {
+    /// Characters of the last validated run that were not read yet.
+    chars: str::Chars<'a>,
+    /// Bytes after the last validated run.
+    rest: &'a [u8],
+}
+
+/// Number of bytes [`SliceRead`] validates at a time, few enough that they are still
+/// in cache when they are parsed.
+const RUN_LEN: usize = 64 * 1024;
+
+impl<'a> SliceRead<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            chars: "".chars(),
+            rest: bytes,
+        }
+    }
+
+    /// Validates the next run and returns its first character.
+    #[cold]
+    fn next_run(&mut self) -> Result<Option<char>, SourceError> {
+        if self.rest.is_empty() {
+            return Ok(None);
+        }
+        // a run cut in the middle of a character ends before it, which starts the next
+        // one, so only a run that fails right away holds invalid input
+        let run = &self.rest[..self.rest.len().min(RUN_LEN)];
+        let valid = match str::from_utf8(run) {
+            Ok(valid) => valid,
+            Err(err) if err.valid_up_to() > 0 => str::from_utf8(&run[..err.valid_up_to()])
+                .expect("bytes up to valid_up_to should be valid UTF-8"),
+            Err(_) => return Err(SourceError::InvalidUtf8),
+        };
+        self.rest = &self.rest[valid.len()..];
+        self.chars = valid.chars();
+        Ok(self.chars.next())
+    }
+}
+
+impl Source for SliceRead<'_> {
+    #[inline]
+    fn next_char(&mut self) -> Result<Option<char>, SourceError> {
+        match self.chars.next() {
+            Some(ch) => Ok(Some(ch)),
+            None => self.next_run(),
+        }
+    }
+}
+
+/// A [`Source`] over UTF-8 bytes from an [`io::Read`], validated as they are read.
+/// Reads are buffered, so the reader does not need to be buffered itself.
+pub struct IoRead<R> {
+    reader: R,
+    buf: Box<[u8]>,
+    start: usize,
+    /// End of the bytes from `start` that are known to be valid UTF-8.
+    valid: usize,
+    end: usize,
+    eof: bool,
+}
+
+const IO_BUF_LEN: usize = 8 * 1024;
+
+impl<R: io::Read> IoRead<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: vec![0; IO_BUF_LEN].into_boxed_slice(),
+            start: 0,
+            valid: 0,
+            end: 0,
+            eof: false,
+        }
+    }
+
+    /// Returns the underlying reader. Bytes that were read ahead into the buffer are lost.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Reads until the buffer holds a whole character or the input ends. Nothing more
+    /// is read than the next character needs, so parsing a value from a socket does not
+    /// wait for input after it.
+    fn fill(&mut self) -> io::Result<()> {
+        if self.buf.len() - self.start < 4 {
+            self.buf.copy_within(self.start..self.end, 0);
+            self.end -= self.start;
+            self.start = 0;
+        }
+        while !self.eof && self.end - self.start < self.needed() {
+            match self.reader.read(&mut self.buf[self.end..]) {
+                Ok(0) => self.eof = true,
+                Ok(n) => self.end += n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads and validates the next run, returning its first character.
+    #[cold]
+    fn next_run(&mut self) -> Result<Option<char>, SourceError> {
+        self.fill().map_err(SourceError::Io)?;
+        // validate everything buffered at once, up to a character cut short or invalid
+        // bytes
+        let bytes = &self.buf[self.start..self.end];
+        let len = match str::from_utf8(bytes) {
+            Ok(_) => bytes.len(),
+            Err(err) => err.valid_up_to(),
+        };
+        if bytes.is_empty() {
+            return Ok(None);
+        }
+        // the buffer holds the whole next character unless the input ended
+        if len == 0 {
+            return Err(SourceError::InvalidUtf8);
+        }
+        self.valid = self.start + len;
+        Ok(Some(self.next_valid()))
+    }
+
+    /// Reads the next character, which has been validated.
+    fn next_valid(&mut self) -> char {
+        let b = self.buf[self.start];
+        if b.is_ascii() {
+            self.start += 1;
+            return char::from(b);
+        }
+        let (ch, len) = decode(&self.buf[self.start..self.valid])
+            .ok()
+            .flatten()
+            .expect("validated bytes should hold a character");
+        self.start += len;
+        ch
+    }
+
+    /// Number of bytes the next character takes, as far as the buffer tells.
+    fn needed(&self) -> usize {
+        match self.buf[self.start..self.end].first() {
+            None => 1,
+            Some(&b) => utf8_len(b),
+        }
+    }
+}
+
+/// Length of the UTF-8 sequence starting with `b`, 1 for bytes that cannot start one.
+fn utf8_len(b: u8) -> usize {
+    match b.leading_ones() {
+        2 => 2,
+        3 => 3,
+        4 => 4,
+        _ => 1,
+    }
+}
+
+impl<R: io::Read> Source for IoRead<R> {
+    #[inline]
+    fn next_char(&mut self) -> Result<Option<char>, SourceError> {
+        if self.start == self.valid {
+            return self.next_run();
+        }
+        Ok(Some(self.next_valid()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(mut src: impl Source) -> Result<String, SourceError> {
+        let mut out = String::new();
+        while let Some(ch) = src.next_char()? {
+            out.push(ch);
+        }
+        Ok(out)
+    }
+
+    /// Returns at most one byte per read, so characters are split across reads.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl io::Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let Some((&b, rest)) = self.0.split_first() else {
+                return Ok(0);
+            };
+            buf[0] = b;
+            self.0 = rest;
+            Ok(1)
+        }
+    }
+
+    #[test]
+    fn slice_read_works() {
+        let src = "a é € 😀";
+        assert_eq!(chars(SliceRead::new(src.as_bytes())).unwrap(), src);
+        assert_eq!(chars(SliceRead::new(b"")).unwrap(), "");
+
+        for invalid in [
+            &b"\xff"[..],
+            b"\x80",
+            b"\xc3",
+            b"\xc0\x80",
+            b"\xed\xa0\x80",
+            b"\xf4\x90\x80\x80",
+            b"\xe2\x82",
+        ] {
+            let err = chars(SliceRead::new(invalid)).unwrap_err();
+            assert!(matches!(err, SourceError::InvalidUtf8), "{invalid:?}");
+        }
+
+        // runs are validated in pieces that may end inside a character
+        for pad in RUN_LEN - 3..=RUN_LEN {
+            let src = format!("{}é€😀", "a".repeat(pad));
+            assert_eq!(chars(SliceRead::new(src.as_bytes())).unwrap(), src);
+        }
+    }
+
+    #[test]
+    fn sources_read_valid_characters_before_invalid_ones() {
+        let src = b"a\xc3\xa9\xff";
+        let mut slice = SliceRead::new(src);
+        let mut io = IoRead::new(&src[..]);
+        for source in [&mut slice as &mut dyn Source, &mut io] {
+            assert_eq!(source.next_char().unwrap(), Some('a'));
+            assert_eq!(source.next_char().unwrap(), Some('é'));
+            assert!(matches!(source.next_char(), Err(SourceError::InvalidUtf8)));
+        }
+    }
+
+    #[test]
+    fn io_read_works() {
+        let src = "a é € 😀".repeat(3000);
+        assert_eq!(chars(IoRead::new(src.as_bytes())).unwrap(), src);
+        assert_eq!(chars(IoRead::new(Trickle(src.as_bytes()))).unwrap(), src);
+
+        let err = chars(IoRead::new(Trickle(b"ab\xe2\x82"))).unwrap_err();
+        assert!(matches!(err, SourceError::InvalidUtf8));
+    }
+}
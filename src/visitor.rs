@@ -0,0 +1,182 @@
+use std::{error, fmt};
+
+use super::{JsonParserError, Number, Position};
+
+/// Callbacks for the parts of a json value, driven by [`JsonParser::visit`] in document
+/// order. They receive the same data as the [`Event`](crate::Event)s of an
+/// [`EventParser`](crate::EventParser), so callers can build their own data structures
+/// without an intermediate [`Value`](crate::Value).
+///
+/// Every callback does nothing by default. Returning an error stops parsing right away.
+///
+/// [`JsonParser::visit`]: crate::JsonParser::visit
+pub trait Visitor {
+    type Error;
+
+    fn start_object(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// The key of an object member, followed by the callbacks for its value.
+    fn key(&mut self, key: String) -> Result<(), Self::Error> {
+        let _ = key;
+        Ok(())
+    }
+
+    fn end_object(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn start_array(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn end_array(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn string(&mut self, s: String) -> Result<(), Self::Error> {
+        let _ = s;
+        Ok(())
+    }
+
+    fn number(&mut self, n: Number) -> Result<(), Self::Error> {
+        let _ = n;
+        Ok(())
+    }
+
+    fn bool(&mut self, b: bool) -> Result<(), Self::Error> {
+        let _ = b;
+        Ok(())
+    }
+
+    fn null(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Why [`JsonParser::visit`](crate::JsonParser::visit) stopped.
+#[derive(Debug)]
+pub enum VisitError<E> {
+    /// The input is not valid json.
+    Parse(JsonParserError),
+    /// A [`Visitor`] callback failed for the part of the value starting at `position`.
+    Visitor { error: E, position: Position },
+}
+
+impl<E: fmt::Display> fmt::Display for VisitError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VisitError::Parse(err) => err.fmt(f),
+            VisitError::Visitor { error, position } => write!(
+                f,
+                "Visit json error at line {} column {}: {error}",
+                position.line(),
+                position.column()
+            ),
+        }
+    }
+}
+
+impl<E: error::Error + 'static> error::Error for VisitError<E> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            VisitError::Parse(err) => Some(err),
+            VisitError::Visitor { error, .. } => Some(error),
+        }
+    }
+}
+
+impl<E> From<JsonParserError> for VisitError<E> {
+    fn from(err: JsonParserError) -> Self {
+        VisitError::Parse(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ErrorKind, JsonParser};
+
+    /// Collects the `id` and `name` of every record straight into columns.
+    #[derive(Default)]
+    struct Columns {
+        ids: Vec<u64>,
+        names: Vec<String>,
+        depth: usize,
+        key: Option<String>,
+    }
+
+    impl Visitor for Columns {
+        type Error = String;
+
+        fn start_object(&mut self) -> Result<(), String> {
+            self.depth += 1;
+            Ok(())
+        }
+
+        fn end_object(&mut self) -> Result<(), String> {
+            self.depth -= 1;
+            Ok(())
+        }
+
+        fn key(&mut self, key: String) -> Result<(), String> {
+            self.key = (self.depth == 1).then_some(key);
+            Ok(())
+        }
+
+        fn string(&mut self, s: String) -> Result<(), String> {
+            if self.key.as_deref() == Some("name") {
+                self.names.push(s);
+            }
+            Ok(())
+        }
+
+        fn number(&mut self, n: Number) -> Result<(), String> {
+            if self.key.as_deref() == Some("id") {
+                let id = n.as_u64().ok_or_else(|| format!("invalid id {n}"))?;
+                self.ids.push(id);
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn visitor_works() {
+        let src = r#"[
+            {"id": 1, "name": "a", "extra": {"id": 99, "name": "nested"}},
+            {"name": "b", "id": 2, "tags": ["x", null, true]}
+        ]"#;
+        let mut columns = Columns::default();
+        JsonParser::new(src.chars()).visit(&mut columns).unwrap();
+        assert_eq!(columns.ids, [1, 2]);
+        assert_eq!(columns.names, ["a", "b"]);
+    }
+
+    #[test]
+    fn visitor_aborts_early() {
+        let src = "[{\"id\": 1},\n {\"id\": -2}, {\"id\": ]";
+        let mut columns = Columns::default();
+        let mut parser = JsonParser::new(src.chars());
+        let err = parser.visit(&mut columns).unwrap_err();
+        let VisitError::Visitor { error, position } = &err else {
+            panic!("expected a visitor error, got {err:?}");
+        };
+        assert_eq!(error, "invalid id -2");
+        assert_eq!((position.line(), position.column()), (2, 9));
+        assert_eq!(
+            err.to_string(),
+            "Visit json error at line 2 column 9: invalid id -2"
+        );
+        // nothing after the failed callback is visited, so the invalid json is not reached
+        assert_eq!(columns.ids, [1]);
+
+        let err = JsonParser::new("[{\"id\": 1}, {\"id\": ]".chars())
+            .visit(&mut Columns::default())
+            .unwrap_err();
+        let VisitError::Parse(err) = err else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(err.kind(), ErrorKind::UnexpectedChar);
+    }
+}